@@ -0,0 +1,102 @@
+//! Spawn cost of the walls of every shipped room template, with one collider
+//! per wall cell against one per merged `WallRect`.
+
+use bevy::{
+    core::TaskPoolPlugin,
+    hierarchy::HierarchyPlugin,
+    prelude::{App, Transform, TransformBundle},
+    time::Time,
+    transform::TransformPlugin,
+};
+use bevy_rapier2d::prelude::{Collider, NoUserData, RapierPhysicsPlugin, RigidBody};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use roguelike::map::{
+    templates::{RoomTemplateRegistry, ROOM_TEMPLATE_DIR},
+    walls::{merge_wall_cells, wall_cells, WallRect},
+};
+
+/// Size of a cell of the templates, in LDtk pixels.
+const GRID_SIZE: f32 = 128.;
+
+/// A headless app with the physics, which builds the colliders spawned
+/// before its next update.
+fn physics_app() -> App {
+    let mut app = App::new();
+    app.add_plugin(TaskPoolPlugin::default())
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
+        .init_resource::<Time>();
+    app.update();
+    app
+}
+
+/// Spawns a fixed collider for every rectangle and lets Rapier build them.
+fn spawn_walls(app: &mut App, rects: &[WallRect]) {
+    for rect in rects {
+        let (width, height) = rect.size();
+        app.world.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(
+                width as f32 * GRID_SIZE / 2.,
+                height as f32 * GRID_SIZE / 2.,
+            ),
+            TransformBundle::from_transform(Transform::from_translation(
+                (rect.center() * GRID_SIZE).extend(0.),
+            )),
+        ));
+    }
+    app.update();
+}
+
+fn wall_colliders(c: &mut Criterion) {
+    let registry = RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap();
+    let cells: Vec<Vec<(i32, i32)>> = registry
+        .templates()
+        .iter()
+        .map(|template| wall_cells(&template.path).unwrap())
+        .collect();
+
+    // A cell is the same as a rectangle of one cell
+    let per_cell: Vec<WallRect> = cells
+        .iter()
+        .flatten()
+        .map(|&(x, y)| WallRect {
+            left: x,
+            right: x,
+            bottom: y,
+            top: y,
+        })
+        .collect();
+    let merged: usize = cells
+        .iter()
+        .map(|cells| merge_wall_cells(cells.iter().copied()).len())
+        .sum();
+
+    let mut group = c.benchmark_group("spawn wall colliders");
+    group.bench_function(format!("per cell ({})", per_cell.len()), |b| {
+        b.iter_batched(
+            physics_app,
+            |mut app| spawn_walls(&mut app, &per_cell),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function(format!("merged ({})", merged), |b| {
+        b.iter_batched(
+            physics_app,
+            // Merging is part of the cost, it's done when a level spawns
+            |mut app| {
+                let rects: Vec<WallRect> = cells
+                    .iter()
+                    .flat_map(|cells| merge_wall_cells(cells.iter().copied()))
+                    .collect();
+                spawn_walls(&mut app, &rects)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, wall_colliders);
+criterion_main!(benches);
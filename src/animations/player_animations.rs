@@ -8,9 +8,12 @@ use bevy::{
     reflect::Reflect,
     sprite::{TextureAtlas, TextureAtlasSprite},
 };
-use bevy_rapier2d::prelude::Velocity;
 
-use crate::entities::player::Player;
+use crate::entities::{
+    character::CharacterVelocity,
+    combat::Melee,
+    player::{Climbing, Facing, Player},
+};
 
 use super::sprite_animation::SpriteAnimation;
 
@@ -23,6 +26,8 @@ pub enum Animation {
     Crouch,
     CrouchWalk,
     LookUp,
+    Climb,
+    Attack,
 }
 
 #[derive(Resource)]
@@ -52,6 +57,14 @@ impl FromWorld for PlayerAnimations {
             None,
             Some(Vec2::new(0., 9.)),
         );
+        let attack_atlas = TextureAtlas::from_grid(
+            asset_server.load("GoldenDude/attack_128x128.png"),
+            Vec2::splat(128.),
+            4,
+            1,
+            None,
+            Some(Vec2::new(0., 9.)),
+        );
         let jump_atlas = TextureAtlas::from_grid(
             asset_server.load("GoldenDude/jump_128x128.png"),
             Vec2::splat(128.),
@@ -92,6 +105,14 @@ impl FromWorld for PlayerAnimations {
             None,
             Some(Vec2::new(0., 9.)),
         );
+        let climb_atlas = TextureAtlas::from_grid(
+            asset_server.load("GoldenDude/climb_128x128.png"),
+            Vec2::splat(128.),
+            2,
+            1,
+            None,
+            Some(Vec2::new(0., 9.)),
+        );
 
         let mut texture_atlas = world.resource_mut::<Assets<TextureAtlas>>();
         map.add(
@@ -110,6 +131,15 @@ impl FromWorld for PlayerAnimations {
                 frame_time: 1. / 10.,
             },
         );
+        // About as long as the swing of a `Melee`
+        map.add(
+            Animation::Attack,
+            texture_atlas.add(attack_atlas),
+            SpriteAnimation {
+                len: 4,
+                frame_time: 1. / 25.,
+            },
+        );
         map.add(
             Animation::Jump,
             texture_atlas.add(jump_atlas),
@@ -150,6 +180,14 @@ impl FromWorld for PlayerAnimations {
                 frame_time: 1.,
             },
         );
+        map.add(
+            Animation::Climb,
+            texture_atlas.add(climb_atlas),
+            SpriteAnimation {
+                len: 2,
+                frame_time: 1. / 6.,
+            },
+        );
 
         map
     }
@@ -171,21 +209,25 @@ pub fn change_player_animation(
             &mut Handle<TextureAtlas>,
             &mut SpriteAnimation,
             &mut TextureAtlasSprite,
-            &Velocity,
+            &CharacterVelocity,
+            &Facing,
+            Option<&Climbing>,
+            &Melee,
         ),
         With<Player>,
     >,
     input: Res<Input<KeyCode>>,
     animations: Res<PlayerAnimations>,
 ) {
-    let (mut atlas, mut animation, mut sprite, velocity) = player.single_mut();
-    if velocity.linvel.x < 0. {
-        sprite.flip_x = true;
-    } else if velocity.linvel.x > 0. {
-        sprite.flip_x = false;
-    }
+    let (mut atlas, mut animation, mut sprite, velocity, facing, climbing, melee) =
+        player.single_mut();
+    sprite.flip_x = facing.0 < 0.;
 
-    let set = if velocity.linvel.y > 0.01 {
+    let set = if climbing.is_some() {
+        Animation::Climb
+    } else if melee.swinging() {
+        Animation::Attack
+    } else if velocity.linvel.y > 0.01 {
         Animation::Jump
     } else if velocity.linvel.y < -10. {
         Animation::Fall
@@ -2,7 +2,8 @@ use bevy::{
     prelude::{Bundle, Component},
     sprite::SpriteBundle,
 };
-use bevy_ecs_ldtk::LdtkIntCell;
+use bevy_ecs_ldtk::{IntGridCell, LdtkIntCell};
+use bevy_rapier2d::prelude::{CollisionGroups, Group, Sensor};
 
 use super::collision::CollisionBundle;
 
@@ -24,7 +25,7 @@ impl BlockBundle {
 #[derive(Clone, Default, Debug, Component)]
 pub struct Wall;
 
-#[derive(Clone, Default, Bundle, LdtkIntCell)]
+#[derive(Clone, Default, Bundle)]
 pub struct WallBundle {
     _w: Wall,
 
@@ -41,10 +42,48 @@ impl WallBundle {
     }
 }
 
+/// A wall cell of an LDtk IntGrid layer. Cells have no collider of their
+/// own, `spawn_wall_colliders` merges them into larger ones.
+#[derive(Clone, Default, Bundle, LdtkIntCell)]
+pub struct WallCellBundle {
+    _w: Wall,
+}
+
+/// Collision group of the `OneWayPlatform`s, so that the character
+/// controller can ignore them.
+pub const PLATFORM_GROUP: Group = Group::GROUP_2;
+
+/// A platform that can be jumped up through, and dropped down from with
+/// `PlayerInput::Fall`, see `pass_through_platforms`.
+#[derive(Clone, Default, Debug, Component)]
+pub struct OneWayPlatform;
+
+#[derive(Clone, Bundle, LdtkIntCell)]
+pub struct OneWayPlatformBundle {
+    _p: OneWayPlatform,
+
+    #[with(platform_group)]
+    collision_groups: CollisionGroups,
+
+    #[from_int_grid_cell]
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+fn platform_group(_: IntGridCell) -> CollisionGroups {
+    CollisionGroups::new(PLATFORM_GROUP, Group::ALL)
+}
+
 #[derive(Clone, Default, Debug, Component)]
 pub struct Climbable;
 
+/// A ladder cell, a sensor the player can climb while touching it, see
+/// `entities::player::climb`.
 #[derive(Bundle, Default, Clone, LdtkIntCell)]
 pub struct LadderBundle {
     climbable: Climbable,
+    sensor: Sensor,
+
+    #[bundle]
+    collision: CollisionBundle,
 }
@@ -0,0 +1,364 @@
+//! The player's body: a kinematic rigid body moved by the
+//! `KinematicCharacterController`. It keeps its own `CharacterVelocity` and
+//! falls with the gravity of the `RapierConfiguration`, the controller takes
+//! care of the collisions, the slopes and the steps.
+
+use bevy::{
+    prelude::{Bundle, Component, Entity, GlobalTransform, Query, Res, Vec2},
+    reflect::Reflect,
+    time::Time,
+};
+use bevy_rapier2d::prelude::{
+    ActiveCollisionTypes, CharacterAutostep, CharacterLength, Collider, GravityScale,
+    KinematicCharacterController, KinematicCharacterControllerOutput, QueryFilter,
+    QueryFilterFlags, RapierConfiguration, RapierContext, RigidBody,
+};
+
+/// Velocity of a character, in world units per second. Rapier's `Velocity`
+/// can't be used, it's overwritten with the one of the kinematic body.
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq)]
+pub struct CharacterVelocity {
+    pub linvel: Vec2,
+}
+
+/// Whether the character stands on something, see `update_characters`.
+#[derive(Reflect, Component, Default, Debug, Clone)]
+pub struct Grounded(pub bool);
+
+/// Fastest a character falls.
+pub const MAX_FALL_SPEED: f32 = 3000.0;
+
+/// How far below a character the ground can be for it to stand on it.
+const GROUND_PROBE: f32 = 4.0;
+
+#[derive(Bundle)]
+pub struct CharacterBundle {
+    rigid_body: RigidBody,
+    collider: Collider,
+    collision_types: ActiveCollisionTypes,
+    controller: KinematicCharacterController,
+    output: KinematicCharacterControllerOutput,
+    velocity: CharacterVelocity,
+    gravity_scale: GravityScale,
+    grounded: Grounded,
+}
+
+impl CharacterBundle {
+    pub fn new(collider: Collider) -> Self {
+        Self {
+            rigid_body: RigidBody::KinematicPositionBased,
+            collider,
+            // Kinematic bodies ignore fixed ones by default, sensors included
+            collision_types: ActiveCollisionTypes::default()
+                | ActiveCollisionTypes::KINEMATIC_FIXED,
+            controller: KinematicCharacterController {
+                autostep: Some(CharacterAutostep {
+                    max_height: CharacterLength::Relative(0.2),
+                    min_width: CharacterLength::Relative(0.0),
+                    include_dynamic_bodies: true,
+                }),
+                filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+                ..Default::default()
+            },
+            output: KinematicCharacterControllerOutput::default(),
+            velocity: CharacterVelocity::default(),
+            gravity_scale: GravityScale(1.0),
+            grounded: Grounded::default(),
+        }
+    }
+
+    pub fn player() -> Self {
+        Self::new(Collider::cuboid(36., 50.))
+    }
+}
+
+/// Looks for ground right below the characters, and at how the last move
+/// went: landing or bumping into a ceiling stops the vertical motion.
+///
+/// The controller's own `grounded` flickers on flat ground and misses the
+/// ground it didn't move against, so a short shape-cast, filtered like the
+/// controller, decides instead.
+pub fn update_characters(
+    rapier_context: Res<RapierContext>,
+    mut characters: Query<(
+        Entity,
+        &GlobalTransform,
+        &Collider,
+        &KinematicCharacterController,
+        &KinematicCharacterControllerOutput,
+        &mut CharacterVelocity,
+        &mut Grounded,
+    )>,
+) {
+    for (entity, transform, collider, controller, output, mut velocity, mut grounded) in
+        characters.iter_mut()
+    {
+        let filter = QueryFilter {
+            flags: controller.filter_flags,
+            groups: controller.filter_groups,
+            ..Default::default()
+        }
+        .exclude_collider(entity);
+        // A wall next to the character isn't ground, a slope it can climb is
+        let max_normal = -controller.max_slope_climb_angle.cos();
+        grounded.0 = rapier_context
+            .cast_shape(
+                transform.translation().truncate(),
+                0.,
+                Vec2::NEG_Y,
+                collider,
+                GROUND_PROBE,
+                filter,
+            )
+            .is_some_and(|(_, hit)| hit.normal1.y <= max_normal);
+        if grounded.0 && velocity.linvel.y < 0. {
+            velocity.linvel.y = 0.;
+        }
+
+        // Steps and slopes move the character up, so only a move that fell
+        // well short of its height is a bump
+        let bumped = output.desired_translation.y > 0.
+            && output.effective_translation.y < output.desired_translation.y * 0.5;
+        if bumped && velocity.linvel.y > 0. {
+            velocity.linvel.y = 0.;
+        }
+    }
+}
+
+/// Applies the gravity, and hands the move of this frame to the controller.
+/// Nothing moves while the physics are stopped, by a hit stop for instance.
+pub fn move_characters(
+    mut characters: Query<(
+        &mut KinematicCharacterController,
+        &mut CharacterVelocity,
+        &GravityScale,
+    )>,
+    rapier_config: Res<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (mut controller, mut velocity, gravity_scale) in characters.iter_mut() {
+        if !rapier_config.physics_pipeline_active {
+            controller.translation = None;
+            continue;
+        }
+
+        velocity.linvel += rapier_config.gravity * gravity_scale.0 * dt;
+        velocity.linvel.y = velocity.linvel.y.max(-MAX_FALL_SPEED);
+        controller.translation = Some(velocity.linvel * dt);
+    }
+}
+
+#[cfg(test)]
+use super::headless::{physics_app, position, run_frames, spawn_block, transform_at};
+#[cfg(test)]
+use bevy::prelude::App;
+
+/// A headless app with the physics and the character systems.
+#[cfg(test)]
+fn test_app() -> App {
+    use bevy::prelude::IntoSystemConfigs;
+
+    let mut app = physics_app();
+    app.add_systems((update_characters, move_characters).chain());
+    app
+}
+
+/// Spawns the player's body at `position`, moving at `velocity`.
+#[cfg(test)]
+fn spawn_character(app: &mut App, position: Vec2, velocity: Vec2) -> Entity {
+    let mut character = CharacterBundle::player();
+    character.velocity.linvel = velocity;
+    app.world.spawn((character, transform_at(position))).id()
+}
+
+#[cfg(test)]
+fn grounded(app: &App, character: Entity) -> bool {
+    app.world.get::<Grounded>(character).unwrap().0
+}
+
+#[test]
+fn walks_up_gentle_slopes() {
+    let mut app = test_app();
+    // Ground at `y == 0`, a 30° ramp from `x == 200` to `x == 800`, and a
+    // plateau after it
+    let top = 600. * 30f32.to_radians().tan();
+    spawn_block(&mut app, Collider::cuboid(1000., 50.), Vec2::new(0., -50.));
+    spawn_block(
+        &mut app,
+        Collider::triangle(
+            Vec2::new(200., 0.),
+            Vec2::new(800., 0.),
+            Vec2::new(800., top),
+        ),
+        Vec2::ZERO,
+    );
+    spawn_block(
+        &mut app,
+        Collider::cuboid(500., top / 2.),
+        Vec2::new(1300., top / 2.),
+    );
+
+    let walker = spawn_character(&mut app, Vec2::new(0., 52.), Vec2::new(400., 0.));
+    run_frames(&mut app, 240);
+    let position = position(&app, walker);
+    assert!(position.x > 900., "stuck at {}", position);
+    assert!(position.y > top + 45., "not on the plateau: {}", position);
+    assert!(grounded(&app, walker));
+}
+
+#[test]
+fn stands_still_on_gentle_slopes() {
+    let mut app = test_app();
+    let slope = 30f32.to_radians().tan();
+    spawn_block(
+        &mut app,
+        Collider::triangle(
+            Vec2::new(-1000., 0.),
+            Vec2::new(1000., 0.),
+            Vec2::new(1000., 2000. * slope),
+        ),
+        Vec2::ZERO,
+    );
+
+    // Just above the slope, which it lands on with its right corner
+    let start = Vec2::new(0., (1000. + 36.) * slope + 55.);
+    let character = spawn_character(&mut app, start, Vec2::ZERO);
+    run_frames(&mut app, 120);
+    let position = position(&app, character);
+    assert!((position.x - start.x).abs() < 5., "slid to {}", position);
+    assert!(grounded(&app, character));
+}
+
+#[test]
+fn steps_over_small_ledges_only() {
+    let mut app = test_app();
+    spawn_block(&mut app, Collider::cuboid(2000., 50.), Vec2::new(0., -50.));
+    // An 8px step from `x == 100` on
+    spawn_block(&mut app, Collider::cuboid(1000., 4.), Vec2::new(1100., 4.));
+    let walker = spawn_character(&mut app, Vec2::new(0., 52.), Vec2::new(300., 0.));
+    run_frames(&mut app, 120);
+
+    let position_on_step = position(&app, walker);
+    assert!(position_on_step.x > 400., "stuck at {}", position_on_step);
+    assert!(
+        position_on_step.y > 55.,
+        "not on the step: {}",
+        position_on_step
+    );
+
+    let mut app = test_app();
+    spawn_block(&mut app, Collider::cuboid(2000., 50.), Vec2::new(0., -50.));
+    // An 80px wall from `x == 100` on
+    spawn_block(
+        &mut app,
+        Collider::cuboid(1000., 40.),
+        Vec2::new(1100., 40.),
+    );
+    let walker = spawn_character(&mut app, Vec2::new(0., 52.), Vec2::new(300., 0.));
+    run_frames(&mut app, 120);
+
+    let position_at_wall = position(&app, walker);
+    assert!(
+        position_at_wall.x < 100. - 36.,
+        "went through at {}",
+        position_at_wall
+    );
+    assert!(
+        position_at_wall.y < 55.,
+        "climbed the wall: {}",
+        position_at_wall
+    );
+}
+
+#[test]
+fn bumping_into_ceilings_stops_the_jump() {
+    let mut app = test_app();
+    spawn_block(&mut app, Collider::cuboid(500., 50.), Vec2::new(0., -50.));
+    // A ceiling 100px above the character's head
+    spawn_block(&mut app, Collider::cuboid(500., 50.), Vec2::new(0., 250.));
+    let jumper = spawn_character(&mut app, Vec2::new(0., 52.), Vec2::new(0., 1500.));
+
+    // Without the bump it would still be going up after 10 frames
+    run_frames(&mut app, 10);
+    let velocity = app.world.get::<CharacterVelocity>(jumper).unwrap().linvel;
+    assert!(velocity.y <= 0., "still going up at {}", velocity);
+    assert!(position(&app, jumper).y < 150.);
+
+    run_frames(&mut app, 60);
+    assert!(position(&app, jumper).y < 55.);
+    assert!(grounded(&app, jumper));
+}
+
+#[test]
+fn lands_on_one_way_platforms_and_drops_through_them() {
+    use super::{
+        blocks::{OneWayPlatform, PLATFORM_GROUP},
+        headless::run_until,
+        platforms::pass_through_platforms,
+        player::{Player, PlayerInput},
+    };
+    use bevy::prelude::IntoSystemConfig;
+    use bevy_rapier2d::prelude::{CollisionGroups, Group};
+    use leafwing_input_manager::prelude::ActionState;
+
+    let mut app = test_app();
+    app.add_system(pass_through_platforms.before(update_characters));
+    spawn_block(&mut app, Collider::cuboid(500., 50.), Vec2::new(0., -50.));
+    // A platform from `y == 190` to `y == 210`
+    app.world.spawn((
+        OneWayPlatform,
+        RigidBody::Fixed,
+        Collider::cuboid(200., 10.),
+        CollisionGroups::new(PLATFORM_GROUP, Group::ALL),
+        transform_at(Vec2::new(0., 200.)),
+    ));
+
+    // Jumps about 400px high, through the platform
+    let player = spawn_character(&mut app, Vec2::new(0., 52.), Vec2::new(0., 2000.));
+    app.world
+        .entity_mut(player)
+        .insert((Player, ActionState::<PlayerInput>::default()));
+    run_frames(&mut app, 120);
+    let on_platform = position(&app, player);
+    assert!(on_platform.y > 255., "fell through to {}", on_platform);
+    assert!(grounded(&app, player));
+
+    // Falls through while `Fall` is held, and keeps falling once it's let go
+    // halfway through the platform
+    app.world
+        .get_mut::<ActionState<PlayerInput>>(player)
+        .unwrap()
+        .press(PlayerInput::Fall);
+    let entered = run_until(&mut app, 30, |app| position(app, player).y < 255.);
+    assert!(entered, "stayed on the platform");
+    let inside = position(&app, player);
+    assert!(inside.y > 240., "already below the platform: {}", inside);
+    app.world
+        .get_mut::<ActionState<PlayerInput>>(player)
+        .unwrap()
+        .release(PlayerInput::Fall);
+
+    run_frames(&mut app, 60);
+    let on_ground = position(&app, player);
+    assert!(on_ground.y < 55., "caught by the platform at {}", on_ground);
+    assert!(grounded(&app, player));
+}
+
+#[test]
+fn hangs_in_the_air_while_the_physics_are_stopped() {
+    let mut app = test_app();
+    let character = spawn_character(&mut app, Vec2::new(0., 500.), Vec2::ZERO);
+    app.world
+        .resource_mut::<RapierConfiguration>()
+        .physics_pipeline_active = false;
+    run_frames(&mut app, 10);
+
+    let velocity = app
+        .world
+        .get::<CharacterVelocity>(character)
+        .unwrap()
+        .linvel;
+    assert_eq!(velocity, Vec2::ZERO);
+    assert_eq!(position(&app, character), Vec2::new(0., 500.));
+}
@@ -1,5 +1,5 @@
-use bevy::prelude::{Bundle, Resource};
-use bevy_ecs_ldtk::{EntityInstance, LdtkIntCell};
+use bevy::prelude::{warn, Bundle, Resource, Vec2};
+use bevy_ecs_ldtk::{EntityInstance, IntGridCell, LdtkIntCell};
 use bevy_rapier2d::prelude::{Collider, GravityScale, LockedAxes, RigidBody, Velocity};
 
 #[derive(Bundle, Clone, Debug, LdtkIntCell, Resource)]
@@ -39,28 +39,95 @@ impl CollisionBundle {
             gravity_scale,
         }
     }
+
+    /// A thin slab along the top of a 128px cell.
+    pub fn one_way_platform() -> Self {
+        Self {
+            collider: Collider::compound(vec![(Vec2::new(0., 56.), 0., Collider::cuboid(64., 8.))]),
+            ..Default::default()
+        }
+    }
+
+    /// Spikes along the bottom half of a 128px cell.
+    pub fn spikes() -> Self {
+        Self {
+            collider: Collider::compound(vec![(
+                Vec2::new(0., -32.),
+                0.,
+                Collider::cuboid(64., 32.),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    /// A mob filling its LDtk entity, `width` by `height` pixels.
+    pub fn mob(width: f32, height: f32) -> Self {
+        Self::new(
+            RigidBody::Dynamic,
+            Collider::cuboid(width / 2., height / 2.),
+            LockedAxes::ROTATION_LOCKED_Z,
+            Velocity::default(),
+            GravityScale(1.0),
+        )
+    }
+
+    /// Like `mob`, but floating.
+    pub fn flyer(width: f32, height: f32) -> Self {
+        Self {
+            gravity_scale: GravityScale(0.0),
+            ..Self::mob(width, height)
+        }
+    }
+
+    /// A mob that doesn't move.
+    pub fn turret(width: f32, height: f32) -> Self {
+        Self {
+            collider: Collider::cuboid(width / 2., height / 2.),
+            ..Default::default()
+        }
+    }
+
+    /// A small ball flying straight at `velocity`.
+    pub fn projectile(velocity: Vec2) -> Self {
+        Self::new(
+            RigidBody::Dynamic,
+            Collider::ball(12.),
+            LockedAxes::ROTATION_LOCKED_Z,
+            Velocity::linear(velocity),
+            GravityScale(0.0),
+        )
+    }
 }
 
 impl From<&EntityInstance> for CollisionBundle {
     fn from(entity_instance: &EntityInstance) -> Self {
-        let _rotation_constraints = LockedAxes::ROTATION_LOCKED;
-
         match entity_instance.identifier.as_ref() {
-            "Player" => CollisionBundle::new(
-                RigidBody::Dynamic,
-                Collider::cuboid(36., 50.),
-                LockedAxes::ROTATION_LOCKED_Z,
-                Velocity::default(),
-                GravityScale(1.0),
-            ),
-
             "Mob" => {
-                todo!()
+                CollisionBundle::mob(entity_instance.width as f32, entity_instance.height as f32)
             }
 
-            _ => {
-                todo!()
+            "Flyer" => {
+                CollisionBundle::flyer(entity_instance.width as f32, entity_instance.height as f32)
             }
+
+            "Turret" => {
+                CollisionBundle::turret(entity_instance.width as f32, entity_instance.height as f32)
+            }
+
+            identifier => {
+                warn!("No collider for LDtk entity {:?}", identifier);
+                CollisionBundle::default()
+            }
+        }
+    }
+}
+
+impl From<IntGridCell> for CollisionBundle {
+    fn from(int_grid_cell: IntGridCell) -> Self {
+        match int_grid_cell.value {
+            2 => CollisionBundle::one_way_platform(),
+            4 => CollisionBundle::spikes(),
+            _ => CollisionBundle::default(),
         }
     }
 }
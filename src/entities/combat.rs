@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        BuildChildren, Commands, Component, DespawnRecursiveExt, Entity, EventWriter, Parent,
+        Query, Res, ResMut, Resource, Transform, TransformBundle, Vec2, With,
+    },
+    reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+};
+use bevy_rapier2d::prelude::{
+    ActiveCollisionTypes, Collider, RapierConfiguration, RapierContext, Sensor,
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    enemies::Mob,
+    health::{DamageEvent, Health},
+    player::{Facing, Player, PlayerInput},
+};
+
+/// The player's melee attack: a `Hitbox` in front of it for the length of
+/// `swing`, then nothing until `cooldown` is over.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Melee {
+    pub damage: i32,
+    pub swing: Timer,
+    pub cooldown: Timer,
+}
+
+impl Default for Melee {
+    fn default() -> Self {
+        let finished = |duration| {
+            let mut timer = Timer::new(duration, TimerMode::Once);
+            timer.tick(duration);
+            timer
+        };
+        Self {
+            damage: 1,
+            swing: finished(Duration::from_millis(150)),
+            cooldown: finished(Duration::from_millis(400)),
+        }
+    }
+}
+
+impl Melee {
+    pub fn swinging(&self) -> bool {
+        !self.swing.finished()
+    }
+}
+
+/// Sensor hurting every mob it touches, once.
+#[derive(Component, Debug, Clone)]
+pub struct Hitbox {
+    pub damage: i32,
+    /// `1.` to the right of the player, `-1.` to the left.
+    pub facing: f32,
+    pub hit: Vec<Entity>,
+}
+
+/// Stops the physics for a moment when a hit lands, to make it feel heavier.
+#[derive(Resource, Debug, Clone)]
+pub struct HitStop(pub Timer);
+
+impl Default for HitStop {
+    fn default() -> Self {
+        let mut timer = Timer::new(HitStop::DURATION, TimerMode::Once);
+        timer.tick(HitStop::DURATION);
+        Self(timer)
+    }
+}
+
+impl HitStop {
+    pub const DURATION: Duration = Duration::from_millis(80);
+}
+
+const HITBOX_HALF_SIZE: Vec2 = Vec2::new(40., 40.);
+/// Distance between the player's center and the hitbox's.
+const HITBOX_OFFSET: f32 = 76.;
+
+pub fn attack(
+    mut commands: Commands,
+    mut player: Query<(Entity, &ActionState<PlayerInput>, &Facing, &mut Melee), With<Player>>,
+    time: Res<Time>,
+) {
+    for (entity, input, facing, mut melee) in player.iter_mut() {
+        melee.swing.tick(time.delta());
+        melee.cooldown.tick(time.delta());
+        if !input.just_pressed(PlayerInput::Attack) || !melee.cooldown.finished() {
+            continue;
+        }
+
+        melee.swing.reset();
+        melee.cooldown.reset();
+
+        let facing = facing.0;
+        commands.entity(entity).with_children(|player| {
+            player.spawn((
+                Hitbox {
+                    damage: melee.damage,
+                    facing,
+                    hit: Vec::new(),
+                },
+                Sensor,
+                Collider::cuboid(HITBOX_HALF_SIZE.x, HITBOX_HALF_SIZE.y),
+                // The player's body is kinematic, and turrets are fixed
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_FIXED,
+                TransformBundle::from_transform(Transform::from_xyz(
+                    facing * HITBOX_OFFSET,
+                    0.,
+                    0.,
+                )),
+            ));
+        });
+    }
+}
+
+/// Damages the mobs the hitboxes touch, and removes the hitboxes once the
+/// swing is over.
+pub fn update_hitboxes(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut hitboxes: Query<(Entity, &Parent, &mut Hitbox)>,
+    melee: Query<&Melee>,
+    mobs: Query<(), (With<Mob>, With<Health>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut hit_stop: ResMut<HitStop>,
+) {
+    for (entity, parent, mut hitbox) in hitboxes.iter_mut() {
+        if !melee.get(parent.get()).is_ok_and(Melee::swinging) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        for (collider1, collider2, intersecting) in rapier_context.intersections_with(entity) {
+            let other = if collider1 == entity {
+                collider2
+            } else {
+                collider1
+            };
+            if !intersecting || !mobs.contains(other) || hitbox.hit.contains(&other) {
+                continue;
+            }
+
+            hitbox.hit.push(other);
+            damage_events.send(DamageEvent {
+                target: other,
+                amount: hitbox.damage,
+                source: Some(parent.get()),
+                knockback: Vec2::new(hitbox.facing * 900., 600.),
+            });
+            hit_stop.0.reset();
+        }
+    }
+}
+
+/// Owns `physics_pipeline_active` while `Playing`, the menus take it over
+/// afterwards.
+pub fn hit_stop(
+    mut hit_stop: ResMut<HitStop>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    let stopped = !hit_stop.0.tick(time.delta()).finished();
+    if rapier_config.physics_pipeline_active == stopped {
+        rapier_config.physics_pipeline_active = !stopped;
+    }
+}
+
+/// Ends a hit stop that is still going when the game stops `Playing`, so that
+/// the physics don't stay stopped once the menus resume them.
+pub fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut rapier_config: ResMut<RapierConfiguration>) {
+    let duration = hit_stop.0.duration();
+    hit_stop.0.tick(duration);
+    rapier_config.physics_pipeline_active = true;
+}
@@ -0,0 +1,327 @@
+//! Behaviour of the mobs: every frame they `sense` their surroundings,
+//! `think` about what to do, and `act` on it.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::{Component, Entity, GlobalTransform, Query, Res, Vec2, With, Without},
+    reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+};
+use bevy_rapier2d::prelude::{Collider, QueryFilter, RapierContext, Velocity};
+
+use crate::entities::{health::Invulnerable, player::Player};
+
+/// What a mob is up to.
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq)]
+pub enum MobState {
+    /// Standing still, for a moment.
+    Idle,
+    /// Walking back and forth, turning at walls and ledges.
+    #[default]
+    Patrol,
+    /// Going after the player it can see.
+    Chase,
+    /// Lunging at the player, for a moment.
+    Attack,
+}
+
+/// How a walking mob moves, in world units.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Walker {
+    pub speed: f32,
+    pub chase_speed: f32,
+    pub lunge_speed: f32,
+    /// How far away the player is noticed.
+    pub sight: f32,
+    /// How close the player has to be to lunge at it.
+    pub reach: f32,
+}
+
+impl Default for Walker {
+    fn default() -> Self {
+        Self {
+            speed: 200.,
+            chase_speed: 350.,
+            lunge_speed: 900.,
+            sight: 700.,
+            reach: 150.,
+        }
+    }
+}
+
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Behaviour {
+    pub state: MobState,
+    /// `1.` facing right, `-1.` facing left.
+    pub facing: f32,
+    /// Time left in `Idle` and `Attack`.
+    pub timer: Timer,
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Self {
+            state: MobState::Patrol,
+            facing: 1.,
+            timer: Timer::default(),
+        }
+    }
+}
+
+impl Behaviour {
+    pub const IDLE_TIME: Duration = Duration::from_millis(800);
+    pub const ATTACK_TIME: Duration = Duration::from_millis(500);
+
+    fn enter(&mut self, state: MobState) {
+        self.state = state;
+        let duration = match state {
+            MobState::Idle => Self::IDLE_TIME,
+            MobState::Attack => Self::ATTACK_TIME,
+            MobState::Patrol | MobState::Chase => Duration::ZERO,
+        };
+        self.timer = Timer::new(duration, TimerMode::Once);
+    }
+}
+
+/// What a mob knows about its surroundings, updated by `sense`.
+#[derive(Reflect, Component, Default, Debug, Clone)]
+pub struct Senses {
+    /// Something solid right in front of the mob.
+    pub wall_ahead: bool,
+    /// No ground right in front of the mob.
+    pub ledge_ahead: bool,
+    /// Where the player is, if the mob can see it.
+    pub player: Option<Vec2>,
+}
+
+/// Extra distance the rays reach past the collider.
+const PROBE: f32 = 8.;
+
+/// Half the size of `collider` in world units, if it's a cuboid.
+pub fn half_extents(collider: &Collider, transform: &GlobalTransform) -> Option<Vec2> {
+    let scale = transform.compute_transform().scale.truncate();
+    Some(collider.as_cuboid()?.half_extents() * scale)
+}
+
+/// Whether nothing solid stands between `from` and `to`, ignoring
+/// `exclude`.
+pub fn line_of_sight(
+    rapier_context: &RapierContext,
+    from: Vec2,
+    to: Vec2,
+    exclude: Entity,
+) -> bool {
+    let filter = QueryFilter::only_fixed()
+        .exclude_sensors()
+        .exclude_collider(exclude);
+    let distance = from.distance(to);
+    distance == 0.
+        || rapier_context
+            .cast_ray(from, (to - from) / distance, distance, true, filter)
+            .is_none()
+}
+
+/// Looks for walls and ledges with raycasts against the level, and for the
+/// player within `Walker::sight` and in line of sight.
+pub fn sense(
+    rapier_context: Res<RapierContext>,
+    mut mobs: Query<(
+        Entity,
+        &GlobalTransform,
+        &Collider,
+        &Walker,
+        &Behaviour,
+        &mut Senses,
+    )>,
+    player: Query<&GlobalTransform, With<Player>>,
+) {
+    let player = player.get_single().ok().map(|p| p.translation().truncate());
+
+    for (entity, transform, collider, walker, behaviour, mut senses) in mobs.iter_mut() {
+        let Some(half_extents) = half_extents(collider, transform) else {
+            continue;
+        };
+        let position = transform.translation().truncate();
+        let solid = QueryFilter::only_fixed()
+            .exclude_sensors()
+            .exclude_collider(entity);
+
+        let forward = Vec2::new(behaviour.facing, 0.);
+        senses.wall_ahead = rapier_context
+            .cast_ray(position, forward, half_extents.x + PROBE, true, solid)
+            .is_some();
+
+        let ahead = position + forward * (half_extents.x + PROBE);
+        senses.ledge_ahead = rapier_context
+            .cast_ray(ahead, Vec2::NEG_Y, half_extents.y + PROBE, true, solid)
+            .is_none();
+
+        senses.player = player.filter(|player| {
+            position.distance(*player) <= walker.sight
+                && line_of_sight(&rapier_context, position, *player, entity)
+        });
+    }
+}
+
+/// Moves mobs between the `MobState`s.
+pub fn think(
+    mut mobs: Query<(&GlobalTransform, &Walker, &Senses, &mut Behaviour)>,
+    time: Res<Time>,
+) {
+    for (transform, walker, senses, mut behaviour) in mobs.iter_mut() {
+        let timer_done = behaviour.timer.tick(time.delta()).finished();
+        let position = transform.translation().truncate();
+
+        match (behaviour.state, senses.player) {
+            // Lunges are seen through
+            (MobState::Attack, _) if !timer_done => {}
+            (_, Some(player)) => {
+                behaviour.facing = (player.x - position.x).signum();
+                if position.distance(player) <= walker.reach && behaviour.state != MobState::Attack
+                {
+                    behaviour.enter(MobState::Attack);
+                } else if behaviour.state != MobState::Chase {
+                    behaviour.enter(MobState::Chase);
+                }
+            }
+            (MobState::Chase | MobState::Attack, None) => behaviour.enter(MobState::Idle),
+            (MobState::Idle, None) if timer_done => behaviour.enter(MobState::Patrol),
+            (MobState::Patrol, None) if senses.wall_ahead || senses.ledge_ahead => {
+                behaviour.facing = -behaviour.facing;
+                behaviour.enter(MobState::Idle);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sets the velocity of the mobs according to their `MobState`.
+/// Mobs that were just hit are left to their knockback.
+pub fn act(mut mobs: Query<(&Walker, &Senses, &Behaviour, &mut Velocity), Without<Invulnerable>>) {
+    for (walker, senses, behaviour, mut velocity) in mobs.iter_mut() {
+        let blocked = senses.wall_ahead || senses.ledge_ahead;
+        velocity.linvel.x = match behaviour.state {
+            MobState::Idle => 0.,
+            MobState::Patrol if blocked => 0.,
+            MobState::Patrol => behaviour.facing * walker.speed,
+            MobState::Chase if blocked => 0.,
+            MobState::Chase => behaviour.facing * walker.chase_speed,
+            MobState::Attack if blocked => 0.,
+            // Pushed once, on the frame the lunge starts
+            MobState::Attack if behaviour.timer.elapsed().is_zero() => {
+                behaviour.facing * walker.lunge_speed
+            }
+            MobState::Attack => velocity.linvel.x,
+        };
+    }
+}
+
+#[cfg(test)]
+use crate::entities::headless::{
+    physics_app, position, run_frames, run_until, spawn_block, transform_at,
+};
+#[cfg(test)]
+use bevy::prelude::{App, Transform};
+
+/// A headless app with the physics and the mobs' behaviour.
+#[cfg(test)]
+fn test_app() -> App {
+    use bevy::prelude::IntoSystemConfigs;
+
+    let mut app = physics_app();
+    app.add_systems((sense, think, act).chain());
+    app
+}
+
+/// Spawns a patrolling walker standing on the ground at `x`, facing right.
+#[cfg(test)]
+fn spawn_walker(app: &mut App, x: f32) -> Entity {
+    use crate::entities::collision::CollisionBundle;
+
+    app.world
+        .spawn((
+            Walker::default(),
+            Behaviour::default(),
+            Senses::default(),
+            CollisionBundle::mob(96., 96.),
+            transform_at(Vec2::new(x, 48.)),
+        ))
+        .id()
+}
+
+#[cfg(test)]
+fn behaviour(app: &App, mob: Entity) -> &Behaviour {
+    app.world.get::<Behaviour>(mob).unwrap()
+}
+
+#[test]
+fn patrols_between_walls_and_ledges() {
+    let mut app = test_app();
+    // Ground from `x == -400` to `x == 400`, and a wall from `x == 300` on
+    spawn_block(&mut app, Collider::cuboid(400., 50.), Vec2::new(0., -50.));
+    spawn_block(&mut app, Collider::cuboid(50., 200.), Vec2::new(350., 200.));
+    // The level has to be in the physics before the walker looks at it
+    run_frames(&mut app, 1);
+    let walker = spawn_walker(&mut app, 0.);
+
+    let turned_left = run_until(&mut app, 300, |app| behaviour(app, walker).facing < 0.);
+    assert!(turned_left, "never turned at the wall");
+    let at_wall = position(&app, walker);
+    assert!(at_wall.x > 200., "turned at {}", at_wall);
+    assert_eq!(behaviour(&app, walker).state, MobState::Idle);
+
+    let idle_over = run_until(&mut app, 120, |app| {
+        behaviour(app, walker).state == MobState::Patrol
+    });
+    assert!(idle_over, "never went back to patrolling");
+
+    let turned_right = run_until(&mut app, 600, |app| behaviour(app, walker).facing > 0.);
+    assert!(turned_right, "never turned at the ledge");
+    let at_ledge = position(&app, walker);
+    assert!(at_ledge.x < -300., "turned at {}", at_ledge);
+    assert!(at_ledge.y > 40., "fell off at {}", at_ledge);
+}
+
+#[test]
+fn chases_and_lunges_at_the_player_it_sees() {
+    let mut app = test_app();
+    spawn_block(&mut app, Collider::cuboid(2000., 50.), Vec2::new(0., -50.));
+    run_frames(&mut app, 1);
+    let walker = spawn_walker(&mut app, 0.);
+    // Behind the walker, within its sight
+    let player = app
+        .world
+        .spawn((Player, transform_at(Vec2::new(-600., 48.))))
+        .id();
+
+    run_frames(&mut app, 1);
+    assert_eq!(behaviour(&app, walker).state, MobState::Chase);
+    assert_eq!(behaviour(&app, walker).facing, -1.);
+
+    let lunged = run_until(&mut app, 300, |app| {
+        behaviour(app, walker).state == MobState::Attack
+    });
+    assert!(lunged, "never lunged");
+    let velocity = app.world.get::<Velocity>(walker).unwrap().linvel;
+    assert!(
+        velocity.x < -Walker::default().chase_speed,
+        "lunged at {}",
+        velocity
+    );
+
+    // The player gets away, the lunge is seen through before giving up
+    app.world
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 5000.;
+    let gave_up = run_until(&mut app, 120, |app| {
+        behaviour(app, walker).state == MobState::Idle
+    });
+    assert!(gave_up, "kept chasing");
+    let idle_over = run_until(&mut app, 120, |app| {
+        behaviour(app, walker).state == MobState::Patrol
+    });
+    assert!(idle_over, "never went back to patrolling");
+}
@@ -0,0 +1,139 @@
+use bevy::{
+    prelude::{Bundle, Component, Entity, GlobalTransform, Query, Res, Vec2, With, Without},
+    reflect::Reflect,
+    sprite::SpriteSheetBundle,
+    time::Time,
+};
+use bevy_ecs_ldtk::{EntityInstance, LdtkEntity};
+use bevy_rapier2d::prelude::{RapierContext, Velocity};
+
+use super::{ai::line_of_sight, ContactDamage, Mob};
+use crate::entities::{
+    collision::CollisionBundle,
+    health::{Health, Invulnerable},
+    player::Player,
+};
+
+/// A mob that floats towards the player once it sees it, and hovers in
+/// place otherwise.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Flyer {
+    pub speed: f32,
+    pub sight: f32,
+    /// How quickly it turns towards the player, the higher the sharper.
+    pub steering: f32,
+}
+
+impl Default for Flyer {
+    fn default() -> Self {
+        Self {
+            speed: 300.,
+            sight: 800.,
+            steering: 3.,
+        }
+    }
+}
+
+/// A "Flyer" placed in an LDtk level.
+#[derive(Bundle, LdtkEntity)]
+pub struct FlyerBundle {
+    _m: Mob,
+
+    #[with(flyer_health)]
+    health: Health,
+    contact_damage: ContactDamage,
+
+    flyer: Flyer,
+
+    #[sprite_sheet_bundle]
+    #[bundle]
+    sprite: SpriteSheetBundle,
+
+    #[from_entity_instance]
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+fn flyer_health(_: &EntityInstance) -> Health {
+    Health::new(1)
+}
+
+pub fn fly(
+    rapier_context: Res<RapierContext>,
+    mut flyers: Query<(Entity, &GlobalTransform, &Flyer, &mut Velocity), Without<Invulnerable>>,
+    player: Query<&GlobalTransform, With<Player>>,
+    time: Res<Time>,
+) {
+    let player = player.get_single().ok().map(|p| p.translation().truncate());
+
+    for (entity, transform, flyer, mut velocity) in flyers.iter_mut() {
+        let position = transform.translation().truncate();
+        let target = match player {
+            Some(player)
+                if position.distance(player) <= flyer.sight
+                    && line_of_sight(&rapier_context, position, player, entity) =>
+            {
+                (player - position).normalize_or_zero() * flyer.speed
+            }
+            _ => Vec2::ZERO,
+        };
+
+        let steering = (flyer.steering * time.delta_seconds()).min(1.);
+        velocity.linvel = velocity.linvel.lerp(target, steering);
+    }
+}
+
+#[cfg(test)]
+use crate::entities::headless::{physics_app, position, run_frames, spawn_block, transform_at};
+#[cfg(test)]
+use bevy::prelude::App;
+
+/// Spawns the player at `player`, and a flyer at the origin.
+#[cfg(test)]
+fn spawn_flyer(app: &mut App, player: Vec2) -> Entity {
+    app.world.spawn((Player, transform_at(player)));
+    app.world
+        .spawn((
+            Flyer::default(),
+            CollisionBundle::flyer(80., 80.),
+            transform_at(Vec2::ZERO),
+        ))
+        .id()
+}
+
+#[test]
+fn homes_in_on_the_player_it_sees() {
+    let mut app = physics_app();
+    app.add_system(fly);
+    let player = Vec2::new(500., 300.);
+    let flyer = spawn_flyer(&mut app, player);
+    run_frames(&mut app, 60);
+
+    let velocity = app.world.get::<Velocity>(flyer).unwrap().linvel;
+    let to_player = player - position(&app, flyer);
+    assert!(velocity.length() > 100., "too slow: {}", velocity);
+    assert!(
+        velocity.normalize().dot(to_player.normalize()) > 0.95,
+        "heading {} instead of {}",
+        velocity,
+        to_player
+    );
+    assert!(to_player.length() < player.length());
+}
+
+#[test]
+fn hovers_without_the_player_in_sight() {
+    use bevy_rapier2d::prelude::Collider;
+
+    let mut app = physics_app();
+    app.add_system(fly);
+    // A wall between the flyer and the player, in the physics before the
+    // flyer looks for the player
+    spawn_block(&mut app, Collider::cuboid(20., 200.), Vec2::new(250., 0.));
+    run_frames(&mut app, 1);
+    let flyer = spawn_flyer(&mut app, Vec2::new(500., 0.));
+    run_frames(&mut app, 60);
+
+    assert_eq!(app.world.get::<Velocity>(flyer).unwrap().linvel, Vec2::ZERO);
+    assert_eq!(position(&app, flyer), Vec2::ZERO);
+}
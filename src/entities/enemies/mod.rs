@@ -0,0 +1,137 @@
+pub mod ai;
+pub mod flyer;
+pub mod turret;
+
+use bevy::{
+    prelude::{
+        App, Bundle, Commands, Component, DespawnRecursiveExt, Entity, EventReader, EventWriter,
+        GlobalTransform, IntoSystemConfigs, OnUpdate, Plugin, Query, Res, Vec2, With,
+    },
+    reflect::Reflect,
+    sprite::SpriteSheetBundle,
+};
+use bevy_ecs_ldtk::{prelude::LdtkEntityAppExt, EntityInstance, LdtkEntity};
+use bevy_rapier2d::prelude::RapierContext;
+
+use crate::state::AppState;
+
+use self::{
+    ai::{act, sense, think, Behaviour, Senses, Walker},
+    flyer::{fly, Flyer, FlyerBundle},
+    turret::{fire_turrets, update_projectiles, Projectile, Turret, TurretBundle},
+};
+use super::{
+    collision::CollisionBundle,
+    health::{DamageEvent, Died, Health},
+    player::Player,
+};
+
+#[derive(Component, Reflect, Default, Debug, Clone)]
+pub struct Mob;
+
+/// Damage dealt to the player by touching a mob.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct ContactDamage(pub i32);
+
+impl Default for ContactDamage {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// A "Mob" placed in an LDtk level, a `Walker` drawn with the tile picked for
+/// it in the editor.
+#[derive(Bundle, LdtkEntity)]
+pub struct MobBundle {
+    _m: Mob,
+
+    #[with(mob_health)]
+    health: Health,
+    contact_damage: ContactDamage,
+
+    walker: Walker,
+    behaviour: Behaviour,
+    senses: Senses,
+
+    #[sprite_sheet_bundle]
+    #[bundle]
+    sprite: SpriteSheetBundle,
+
+    #[from_entity_instance]
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+fn mob_health(_: &EntityInstance) -> Health {
+    Health::new(2)
+}
+
+/// Hurts the player when it bumps into a mob, pushing it away from the mob.
+pub fn contact_damage(
+    rapier_context: Res<RapierContext>,
+    mobs: Query<(Entity, &GlobalTransform, &ContactDamage), With<Mob>>,
+    player: Query<(Entity, &GlobalTransform), With<Player>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let Ok((player, player_transform)) = player.get_single() else {
+        return;
+    };
+
+    for (mob, transform, damage) in mobs.iter() {
+        let touching = rapier_context
+            .contact_pair(mob, player)
+            .is_some_and(|contact| contact.has_any_active_contacts());
+        if !touching {
+            continue;
+        }
+
+        let direction = (player_transform.translation().x - transform.translation().x).signum();
+        damage_events.send(DamageEvent {
+            target: player,
+            amount: damage.0,
+            source: Some(mob),
+            knockback: Vec2::new(direction * 800., 1200.),
+        });
+    }
+}
+
+/// Dead mobs are gone for good.
+pub fn despawn_dead_mobs(
+    mut commands: Commands,
+    mut died: EventReader<Died>,
+    mobs: Query<(), With<Mob>>,
+) {
+    for event in died.iter() {
+        if mobs.contains(event.entity) {
+            commands.entity(event.entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<MobBundle>("Mob")
+            .register_ldtk_entity::<FlyerBundle>("Flyer")
+            .register_ldtk_entity::<TurretBundle>("Turret")
+            .add_systems(
+                (sense, think, act)
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_systems(
+                (contact_damage, fly, fire_turrets, update_projectiles)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(despawn_dead_mobs)
+            .register_type::<Mob>()
+            .register_type::<ContactDamage>()
+            .register_type::<Walker>()
+            .register_type::<Behaviour>()
+            .register_type::<Senses>()
+            .register_type::<Flyer>()
+            .register_type::<Turret>()
+            .register_type::<Projectile>();
+    }
+}
@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        Bundle, Color, Commands, Component, DespawnRecursiveExt, Entity, EventWriter,
+        GlobalTransform, Query, Res, Transform, Vec2, With,
+    },
+    reflect::Reflect,
+    sprite::{Sprite, SpriteBundle, SpriteSheetBundle},
+    time::{Time, Timer, TimerMode},
+};
+use bevy_ecs_ldtk::{EntityInstance, LdtkEntity};
+use bevy_rapier2d::prelude::{RapierContext, Sensor, Velocity};
+
+use super::{ai::line_of_sight, Mob};
+use crate::{
+    entities::{
+        collision::CollisionBundle,
+        health::{DamageEvent, Health},
+        player::Player,
+    },
+    map::floor::FloorEntity,
+};
+
+/// A mob that stays put and shoots at the player when it sees it.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Turret {
+    pub range: f32,
+    pub projectile_speed: f32,
+    pub damage: i32,
+    pub cooldown: Timer,
+}
+
+impl Default for Turret {
+    fn default() -> Self {
+        Self {
+            range: 900.,
+            projectile_speed: 700.,
+            damage: 1,
+            cooldown: Timer::new(Duration::from_millis(1500), TimerMode::Repeating),
+        }
+    }
+}
+
+/// A "Turret" placed in an LDtk level.
+#[derive(Bundle, LdtkEntity)]
+pub struct TurretBundle {
+    _m: Mob,
+
+    #[with(turret_health)]
+    health: Health,
+
+    turret: Turret,
+
+    #[sprite_sheet_bundle]
+    #[bundle]
+    sprite: SpriteSheetBundle,
+
+    #[from_entity_instance]
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+fn turret_health(_: &EntityInstance) -> Health {
+    Health::new(3)
+}
+
+/// Flies straight until it hits something or its lifetime runs out.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Projectile {
+    pub damage: i32,
+    /// Whoever fired it, which it can't hit.
+    pub source: Option<Entity>,
+    pub lifetime: Timer,
+}
+
+#[derive(Bundle)]
+pub struct ProjectileBundle {
+    projectile: Projectile,
+    sensor: Sensor,
+    _f: FloorEntity,
+
+    #[bundle]
+    sprite: SpriteBundle,
+
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+impl ProjectileBundle {
+    pub const LIFETIME: Duration = Duration::from_secs(3);
+
+    pub fn new(position: Vec2, velocity: Vec2, damage: i32, source: Option<Entity>) -> Self {
+        Self {
+            projectile: Projectile {
+                damage,
+                source,
+                lifetime: Timer::new(Self::LIFETIME, TimerMode::Once),
+            },
+            sensor: Sensor,
+            _f: FloorEntity,
+            sprite: SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(24.)),
+                    color: Color::ORANGE_RED,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(10.)),
+                ..Default::default()
+            },
+            collision: CollisionBundle::projectile(velocity),
+        }
+    }
+}
+
+pub fn fire_turrets(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut turrets: Query<(Entity, &GlobalTransform, &mut Turret)>,
+    player: Query<&GlobalTransform, With<Player>>,
+    time: Res<Time>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player = player.translation().truncate();
+
+    for (entity, transform, mut turret) in turrets.iter_mut() {
+        if !turret.cooldown.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+        if position.distance(player) > turret.range
+            || !line_of_sight(&rapier_context, position, player, entity)
+        {
+            continue;
+        }
+
+        let velocity = (player - position).normalize_or_zero() * turret.projectile_speed;
+        commands.spawn(ProjectileBundle::new(
+            position,
+            velocity,
+            turret.damage,
+            Some(entity),
+        ));
+    }
+}
+
+/// Despawns projectiles once their lifetime is over, or when they hit
+/// something solid, damaging it if it has `Health`.
+pub fn update_projectiles(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut projectiles: Query<(Entity, &mut Projectile, &Velocity)>,
+    sensors: Query<(), With<Sensor>>,
+    targets: Query<(), With<Health>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut projectile, velocity) in projectiles.iter_mut() {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let hit = rapier_context
+            .intersections_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(collider1, collider2, _)| {
+                if collider1 == entity {
+                    collider2
+                } else {
+                    collider1
+                }
+            })
+            .find(|other| Some(*other) != projectile.source && !sensors.contains(*other));
+        let Some(hit) = hit else {
+            continue;
+        };
+
+        if targets.contains(hit) {
+            damage_events.send(DamageEvent {
+                target: hit,
+                amount: projectile.damage,
+                source: projectile.source,
+                knockback: velocity.linvel.normalize_or_zero() * 600.,
+            });
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+use crate::entities::headless::{physics_app, run_frames, run_until, spawn_block, transform_at};
+#[cfg(test)]
+use bevy::prelude::App;
+
+#[cfg(test)]
+fn projectiles(app: &mut App) -> Vec<Entity> {
+    app.world
+        .query_filtered::<Entity, With<Projectile>>()
+        .iter(&app.world)
+        .collect()
+}
+
+#[test]
+fn fires_on_its_cooldown_at_the_player_in_range() {
+    let mut app = physics_app();
+    app.add_system(fire_turrets);
+    app.world.spawn((
+        Turret::default(),
+        CollisionBundle::turret(112., 112.),
+        transform_at(Vec2::ZERO),
+    ));
+    let player = app
+        .world
+        .spawn((Player, transform_at(Vec2::new(500., 0.))))
+        .id();
+
+    // A shot every 1.5s, 90 frames
+    run_frames(&mut app, 80);
+    assert_eq!(projectiles(&mut app).len(), 0);
+    run_frames(&mut app, 20);
+    assert_eq!(projectiles(&mut app).len(), 1);
+    run_frames(&mut app, 90);
+    assert_eq!(projectiles(&mut app).len(), 2);
+
+    app.world
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 2000.;
+    run_frames(&mut app, 180);
+    assert_eq!(projectiles(&mut app).len(), 2, "fired out of range");
+}
+
+#[test]
+fn projectiles_expire() {
+    let mut app = physics_app();
+    app.add_event::<DamageEvent>()
+        .add_system(update_projectiles);
+    app.world
+        .spawn(ProjectileBundle::new(Vec2::ZERO, Vec2::ZERO, 1, None));
+
+    let lifetime = (ProjectileBundle::LIFETIME.as_secs_f32() * 60.) as u32;
+    run_frames(&mut app, lifetime - 10);
+    assert_eq!(projectiles(&mut app).len(), 1);
+    run_frames(&mut app, 20);
+    assert_eq!(projectiles(&mut app).len(), 0);
+}
+
+#[test]
+fn projectiles_hurt_what_they_hit_and_are_gone() {
+    use bevy::ecs::event::Events;
+    use bevy_rapier2d::prelude::Collider;
+
+    let mut app = physics_app();
+    app.add_event::<DamageEvent>()
+        .add_system(update_projectiles);
+    let target = spawn_block(&mut app, Collider::cuboid(50., 50.), Vec2::new(300., 0.));
+    app.world.entity_mut(target).insert(Health::new(3));
+    app.world.spawn(ProjectileBundle::new(
+        Vec2::ZERO,
+        Vec2::new(700., 0.),
+        2,
+        None,
+    ));
+
+    let damaged = run_until(&mut app, 60, |app| {
+        app.world.resource::<Events<DamageEvent>>().len() > 0
+    });
+    assert!(damaged, "never hit");
+    let events = app.world.resource::<Events<DamageEvent>>();
+    let hit = events.get_reader().iter(events).next().unwrap();
+    assert_eq!(hit.target, target);
+    assert_eq!(hit.amount, 2);
+
+    // Despawned with the same commands that sent the damage
+    assert_eq!(projectiles(&mut app).len(), 0);
+}
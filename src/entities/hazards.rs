@@ -0,0 +1,166 @@
+use bevy::{
+    prelude::{
+        App, Bundle, Commands, Component, DespawnRecursiveExt, Entity, EventWriter,
+        GlobalTransform, IntoSystemConfigs, OnUpdate, Plugin, Query, Res, Transform, Vec2, With,
+        Without,
+    },
+    reflect::Reflect,
+};
+use bevy_ecs_ldtk::{IntGridCell, LdtkIntCell};
+use bevy_rapier2d::prelude::{RapierContext, Sensor};
+
+use super::{
+    character::CharacterVelocity,
+    collision::CollisionBundle,
+    health::{apply_damage, tick_invulnerability, DamageEvent, Died, Health},
+    player::Player,
+};
+use crate::{
+    map::{
+        config::MapConfig,
+        ldtk_setup::CurrentMap,
+        spawn::{entrance_position, PlayerStart},
+    },
+    state::AppState,
+};
+
+/// Hurts whatever has `Health` and touches it.
+#[derive(Reflect, Component, Default, Debug, Clone, Copy, PartialEq)]
+pub enum Hazard {
+    #[default]
+    Spikes,
+    Lava,
+}
+
+impl Hazard {
+    /// Knockback away from the hazard, `direction` being the side of it the
+    /// target is on.
+    pub fn knockback(&self, direction: f32) -> Vec2 {
+        match self {
+            Hazard::Spikes => Vec2::new(direction * 600., 1500.),
+            Hazard::Lava => Vec2::new(direction * 300., 2500.),
+        }
+    }
+
+    pub fn damage(&self) -> i32 {
+        match self {
+            Hazard::Spikes => 1,
+            Hazard::Lava => 2,
+        }
+    }
+}
+
+impl From<IntGridCell> for Hazard {
+    fn from(int_grid_cell: IntGridCell) -> Self {
+        match int_grid_cell.value {
+            5 => Hazard::Lava,
+            _ => Hazard::Spikes,
+        }
+    }
+}
+
+#[derive(Bundle, Default, Clone, LdtkIntCell)]
+pub struct HazardBundle {
+    #[from_int_grid_cell]
+    hazard: Hazard,
+    sensor: Sensor,
+
+    #[from_int_grid_cell]
+    #[bundle]
+    collision: CollisionBundle,
+}
+
+/// Below the map: the player loses a hit point and goes back to the
+/// entrance, anything else is gone.
+#[derive(Component, Default, Debug, Clone)]
+pub struct KillVolume;
+
+pub fn hurt_on_hazards(
+    rapier_context: Res<RapierContext>,
+    targets: Query<(Entity, &GlobalTransform), With<Health>>,
+    hazards: Query<(&Hazard, &GlobalTransform)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (target, target_transform) in targets.iter() {
+        for (collider1, collider2, intersecting) in rapier_context.intersections_with(target) {
+            let other = if collider1 == target {
+                collider2
+            } else {
+                collider1
+            };
+            let Ok((hazard, hazard_transform)) = hazards.get(other) else {
+                continue;
+            };
+            if !intersecting {
+                continue;
+            }
+
+            let direction =
+                (target_transform.translation().x - hazard_transform.translation().x).signum();
+            damage_events.send(DamageEvent {
+                target,
+                amount: hazard.damage(),
+                source: Some(other),
+                knockback: hazard.knockback(direction),
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn fall_out_of_bounds(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    kill_volumes: Query<Entity, With<KillVolume>>,
+    mut players: Query<(Entity, &mut Transform, &mut CharacterVelocity), With<Player>>,
+    others: Query<Entity, (With<Health>, Without<Player>)>,
+    starts: Query<&GlobalTransform, With<PlayerStart>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    map: Option<Res<CurrentMap>>,
+    config: Res<MapConfig>,
+) {
+    let Some(map) = map else {
+        return;
+    };
+
+    for kill_volume in kill_volumes.iter() {
+        let touches = |entity| rapier_context.intersection_pair(kill_volume, entity) == Some(true);
+
+        for (player, mut transform, mut velocity) in players.iter_mut() {
+            if !touches(player) {
+                continue;
+            }
+
+            damage_events.send(DamageEvent {
+                target: player,
+                amount: 1,
+                source: Some(kill_volume),
+                knockback: Vec2::ZERO,
+            });
+            let entrance = entrance_position(&starts, &map.0, &config);
+            transform.translation.x = entrance.x;
+            transform.translation.y = entrance.y;
+            *velocity = CharacterVelocity::default();
+        }
+
+        for other in others.iter().filter(|other| touches(*other)) {
+            commands.entity(other).despawn_recursive();
+        }
+    }
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<Died>()
+            .add_systems(
+                (hurt_on_hazards, fall_out_of_bounds, apply_damage)
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(tick_invulnerability.in_set(OnUpdate(AppState::Playing)))
+            .register_type::<Hazard>();
+    }
+}
@@ -0,0 +1,72 @@
+//! Headless apps with the physics, for the tests of the entities. Frames are
+//! run at 60 per second by `run_frames`, with the game's gravity.
+
+use bevy::{
+    core::TaskPoolPlugin,
+    hierarchy::HierarchyPlugin,
+    prelude::{App, Entity, GlobalTransform, Transform, TransformBundle, Vec2},
+    time::Time,
+    transform::TransformPlugin,
+};
+use bevy_rapier2d::prelude::{
+    Collider, NoUserData, RapierConfiguration, RapierPhysicsPlugin, RigidBody,
+};
+
+pub fn physics_app() -> App {
+    let mut app = App::new();
+    app.add_plugin(TaskPoolPlugin::default())
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
+        .insert_resource(RapierConfiguration {
+            gravity: Vec2::new(0.0, -5000.),
+            ..Default::default()
+        })
+        .init_resource::<Time>();
+    app
+}
+
+pub fn run_frames(app: &mut App, frames: u32) {
+    for _ in 0..frames {
+        let mut time = app.world.resource_mut::<Time>();
+        let last_update = time.last_update().unwrap_or_else(|| time.startup());
+        time.update_with_instant(last_update + std::time::Duration::from_secs_f32(1. / 60.));
+        app.update();
+    }
+}
+
+/// Runs frames until `done`, up to `max_frames`, and returns whether it
+/// happened.
+pub fn run_until(app: &mut App, max_frames: u32, mut done: impl FnMut(&App) -> bool) -> bool {
+    for _ in 0..max_frames {
+        run_frames(app, 1);
+        if done(app) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Already in place, so that systems see it at `position` before the
+/// transforms are propagated.
+pub fn transform_at(position: Vec2) -> TransformBundle {
+    let local = Transform::from_translation(position.extend(0.));
+    TransformBundle {
+        local,
+        global: GlobalTransform::from(local),
+    }
+}
+
+pub fn spawn_block(app: &mut App, collider: Collider, position: Vec2) -> Entity {
+    app.world
+        .spawn((RigidBody::Fixed, collider, transform_at(position)))
+        .id()
+}
+
+pub fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
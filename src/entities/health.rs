@@ -1,20 +1,190 @@
-use bevy::{prelude::Component, reflect::Reflect};
+use std::{collections::HashSet, time::Duration};
 
-#[derive(Reflect, Component, Default, Clone, Copy)]
-pub struct Health(i32);
+use bevy::{
+    prelude::{Commands, Component, Entity, EventReader, EventWriter, Query, Res, Vec2},
+    reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+};
+use bevy_rapier2d::prelude::Velocity;
+
+use super::{character::CharacterVelocity, player::Player};
+
+/// Hit points, always between `0` and `max`.
+#[derive(Reflect, Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+}
+
+/// A single hit point, so that a default `Health` isn't dead from the start.
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
 
 impl Health {
-    pub fn new(hp: i32) -> Self {
-        Self(hp)
+    pub fn new(max: i32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Takes `amount` hit points away, returns whether this was the killing
+    /// blow. Negative amounts are ignored.
+    pub fn damage(&mut self, amount: i32) -> bool {
+        let was_alive = !self.is_dead();
+        self.current = (self.current - amount.max(0)).clamp(0, self.max);
+        was_alive && self.is_dead()
+    }
+
+    /// Gives back `amount` hit points, up to `max`. Negative amounts are
+    /// ignored.
+    pub fn heal(&mut self, amount: i32) {
+        self.current = (self.current + amount.max(0)).clamp(0, self.max);
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
     }
+}
+
+/// Takes `amount` hit points from `target` and pushes it by `knockback`,
+/// unless it's `Invulnerable`. `source` is whatever dealt the damage, if it's
+/// an entity.
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: i32,
+    pub source: Option<Entity>,
+    pub knockback: Vec2,
+}
+
+/// Sent once when `entity`'s health drops to `0`.
+pub struct Died {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+}
+
+/// Damage is ignored until the timer runs out.
+#[derive(Component, Default, Clone)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn new(duration: Duration) -> Self {
+        Self(Timer::new(duration, TimerMode::Once))
+    }
+}
+
+/// How long a hit makes the player `Invulnerable`.
+pub const INVULNERABILITY: Duration = Duration::from_millis(800);
 
-    pub fn hit(&mut self) -> Option<i32> {
-        self.0 -= 1;
+/// How long a hit makes anything else `Invulnerable`. Shorter than the
+/// cooldown of the player's `Melee`, so that no swing is wasted.
+pub const MOB_INVULNERABILITY: Duration = Duration::from_millis(250);
 
-        if self.0 == 0 {
-            None
+pub fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut died: EventWriter<Died>,
+    mut targets: Query<(
+        &mut Health,
+        Option<&mut Velocity>,
+        Option<&mut CharacterVelocity>,
+        Option<&Invulnerable>,
+        Option<&Player>,
+    )>,
+) {
+    // The invulnerability only kicks in once the commands are applied
+    let mut hit = HashSet::new();
+    for event in damage_events.iter() {
+        let Ok((mut health, velocity, character_velocity, invulnerable, player)) =
+            targets.get_mut(event.target)
+        else {
+            continue;
+        };
+        if invulnerable.is_some() || health.is_dead() || !hit.insert(event.target) {
+            continue;
+        }
+
+        if health.damage(event.amount) {
+            died.send(Died {
+                entity: event.target,
+                source: event.source,
+            });
+        }
+        if let Some(mut velocity) = velocity {
+            velocity.linvel = event.knockback;
+        }
+        if let Some(mut velocity) = character_velocity {
+            velocity.linvel = event.knockback;
+        }
+        let duration = if player.is_some() {
+            INVULNERABILITY
         } else {
-            Some(self.0)
+            MOB_INVULNERABILITY
+        };
+        commands
+            .entity(event.target)
+            .insert(Invulnerable::new(duration));
+    }
+}
+
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    mut invulnerable: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable) in invulnerable.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
         }
     }
 }
+
+#[test]
+fn damage_is_clamped() {
+    let mut health = Health::new(4);
+    assert!(!health.damage(1));
+    assert_eq!(health.current, 3);
+
+    assert!(health.damage(10));
+    assert_eq!(health.current, 0);
+    assert!(health.is_dead());
+
+    // Only the first blow kills
+    assert!(!health.damage(1));
+    assert_eq!(health.current, 0);
+}
+
+#[test]
+fn negative_amounts_are_ignored() {
+    let mut health = Health::new(4);
+    assert!(!health.damage(-3));
+    assert_eq!(health.current, 4);
+
+    health.damage(2);
+    health.heal(-3);
+    assert_eq!(health.current, 2);
+}
+
+#[test]
+fn heal_is_clamped() {
+    let mut health = Health::new(4);
+    health.damage(3);
+    health.heal(1);
+    assert_eq!(health.current, 2);
+
+    health.heal(10);
+    assert_eq!(health, Health { current: 4, max: 4 });
+}
+
+#[test]
+fn default_health_is_alive() {
+    let health = Health::default();
+    assert!(!health.is_dead());
+    assert_eq!(health, Health::new(1));
+}
+
+#[test]
+fn mobs_can_be_hit_by_every_swing() {
+    let cooldown = super::combat::Melee::default().cooldown.duration();
+    assert!(MOB_INVULNERABILITY < cooldown);
+}
@@ -1,4 +1,11 @@
 pub mod blocks;
+pub mod character;
 pub mod collision;
+pub mod combat;
+pub mod enemies;
+pub mod hazards;
 pub mod health;
+#[cfg(test)]
+mod headless;
+pub mod platforms;
 pub mod player;
@@ -0,0 +1,46 @@
+use bevy::prelude::{GlobalTransform, Query, Res, With};
+use bevy_rapier2d::prelude::{
+    Collider, CollisionGroups, Group, KinematicCharacterController, QueryFilter, RapierContext,
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    blocks::{OneWayPlatform, PLATFORM_GROUP},
+    character::CharacterVelocity,
+    player::{Player, PlayerInput},
+};
+
+/// The character controller ignores the `OneWayPlatform`s, by collision
+/// group, while the player is going up, holding `PlayerInput::Fall`, or
+/// still inside one of them.
+pub fn pass_through_platforms(
+    rapier_context: Res<RapierContext>,
+    mut players: Query<
+        (
+            &GlobalTransform,
+            &Collider,
+            &mut KinematicCharacterController,
+            &CharacterVelocity,
+            &ActionState<PlayerInput>,
+        ),
+        With<Player>,
+    >,
+    platforms: Query<(), With<OneWayPlatform>>,
+) {
+    for (transform, collider, mut controller, velocity, input) in players.iter_mut() {
+        let inside_platform = || {
+            let is_platform = |entity| platforms.contains(entity);
+            let filter = QueryFilter::default()
+                .exclude_sensors()
+                .predicate(&is_platform);
+            rapier_context
+                .intersection_with_shape(transform.translation().truncate(), 0., collider, filter)
+                .is_some()
+        };
+
+        let passing =
+            input.pressed(PlayerInput::Fall) || velocity.linvel.y > 0. || inside_platform();
+        controller.filter_groups =
+            passing.then(|| CollisionGroups::new(Group::ALL, Group::ALL - PLATFORM_GROUP));
+    }
+}
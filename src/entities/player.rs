@@ -1,51 +1,218 @@
-use super::{collision::CollisionBundle, health::Health};
+use super::{
+    blocks::Climbable,
+    character::{move_characters, update_characters, CharacterBundle, CharacterVelocity, Grounded},
+    combat::{attack, end_hit_stop, hit_stop, update_hitboxes, HitStop, Melee},
+    health::{Died, Health},
+    platforms::pass_through_platforms,
+};
 use crate::{
     animations::{
         player_animations::{Animation, PlayerAnimations},
         sprite_animation::{FrameTime, SpriteAnimation},
     },
+    map::{
+        spawn::{AwaitingLevel, PlayerStartBundle},
+        Sandbox,
+    },
+    state::{AppState, RestartRun},
     AnimationPlugin, CameraTest,
 };
 use bevy::{
     prelude::{
-        error, App, Bundle, Commands, Component, KeyCode, Plugin, Query, Res, Transform, Vec2,
-        Vec3, With, Without,
+        error, info, on_event, App, Bundle, Commands, Component, DespawnRecursiveExt, Entity,
+        EventReader, IntoSystemAppConfig, IntoSystemConfig, IntoSystemConfigs, KeyCode,
+        MouseButton, NextState, OnEnter, OnExit, OnUpdate, Plugin, Query, ReflectResource, Res,
+        ResMut, Resource, Transform, Vec3, With, Without,
     },
     reflect::Reflect,
     sprite::{SpriteSheetBundle, TextureAtlasSprite},
     time::Time,
-    window::Window,
-};
-use bevy_ecs_ldtk::LdtkEntity;
-use bevy_rapier2d::prelude::{
-    CharacterAutostep, CharacterLength, Collider, GravityScale, KinematicCharacterController,
-    KinematicCharacterControllerOutput, LockedAxes, RigidBody, Velocity,
 };
+use bevy_ecs_ldtk::prelude::LdtkEntityAppExt;
+use bevy_rapier2d::prelude::{GravityScale, RapierContext};
 use leafwing_input_manager::{
     prelude::{ActionState, InputMap},
     Actionlike, InputManagerBundle,
 };
 
-#[derive(Reflect, Component, Default, Debug, Clone)]
-pub struct Jump(pub bool, f32);
+/// Tunables of the player's jump, in world units and seconds.
+#[derive(Reflect, Resource, Debug, Clone)]
+#[reflect(Resource)]
+pub struct JumpConfig {
+    /// Upwards speed at the start of a jump.
+    pub speed: f32,
+    /// How long after leaving the ground a jump still works.
+    pub coyote_time: f32,
+    /// How long before landing a jump press is remembered.
+    pub buffer_time: f32,
+    /// Part of the upwards speed kept when the jump is let go early.
+    pub cut: f32,
+}
+
+impl Default for JumpConfig {
+    fn default() -> Self {
+        Self {
+            speed: 1900.,
+            coyote_time: 0.1,
+            buffer_time: 0.12,
+            cut: 0.4,
+        }
+    }
+}
+
+/// Where the player is in its jump, see `JumpConfig`.
+#[derive(Reflect, Component, Debug, Clone)]
+pub struct Jump {
+    /// Seconds since the player last stood on the ground.
+    pub since_grounded: f32,
+    /// Seconds since `PlayerInput::Jump` was last pressed.
+    pub since_pressed: f32,
+    /// Going up from a jump that can still be cut short.
+    pub rising: bool,
+}
 
+impl Default for Jump {
+    fn default() -> Self {
+        Self {
+            since_grounded: f32::INFINITY,
+            since_pressed: f32::INFINITY,
+            rising: false,
+        }
+    }
+}
+
+impl Jump {
+    /// Advances the jump by `dt` seconds and returns the new vertical speed.
+    /// `pressed` is whether the jump was pressed this frame, `held` whether
+    /// it's still down.
+    pub fn update(
+        &mut self,
+        config: &JumpConfig,
+        grounded: bool,
+        pressed: bool,
+        held: bool,
+        dt: f32,
+        velocity: f32,
+    ) -> f32 {
+        if grounded && velocity <= 0. {
+            self.since_grounded = 0.;
+            self.rising = false;
+        } else {
+            self.since_grounded += dt;
+        }
+        if pressed {
+            self.since_pressed = 0.;
+        } else {
+            self.since_pressed += dt;
+        }
+
+        if self.since_pressed <= config.buffer_time && self.since_grounded <= config.coyote_time {
+            self.since_pressed = f32::INFINITY;
+            self.since_grounded = f32::INFINITY;
+            self.rising = true;
+            return config.speed;
+        }
+
+        if self.rising && (!held || velocity <= 0.) {
+            self.rising = false;
+            if velocity > 0. {
+                return velocity * config.cut;
+            }
+        }
+        velocity
+    }
+}
+
+/// Top running speed.
 #[derive(Reflect, Component, Default, Debug, Clone)]
 pub struct Speed(pub f32);
 
-#[derive(Bundle, LdtkEntity)]
+/// `1.` when the player last moved right, `-1.` left. Taken from the input,
+/// so that a knockback doesn't turn the player around.
+#[derive(Reflect, Component, Debug, Clone, Copy)]
+pub struct Facing(pub f32);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+/// Tunables of the player's running, in world units and seconds. Turning
+/// around multiplies the acceleration by `turn_boost`, and going faster
+/// than wanted, say after a knockback, slows down with the deceleration.
+#[derive(Reflect, Resource, Debug, Clone)]
+#[reflect(Resource)]
+pub struct MovementConfig {
+    pub ground_acceleration: f32,
+    pub ground_deceleration: f32,
+    pub air_acceleration: f32,
+    pub air_deceleration: f32,
+    pub turn_boost: f32,
+    /// Top speed while crouching.
+    pub crouch_speed: f32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            ground_acceleration: 6000.,
+            ground_deceleration: 8000.,
+            air_acceleration: 3000.,
+            air_deceleration: 1500.,
+            turn_boost: 2.,
+            crouch_speed: 240.,
+        }
+    }
+}
+
+impl MovementConfig {
+    /// Brings the horizontal speed `velocity` closer to `target` over `dt`
+    /// seconds.
+    pub fn accelerate(&self, velocity: f32, target: f32, grounded: bool, dt: f32) -> f32 {
+        let (acceleration, deceleration) = if grounded {
+            (self.ground_acceleration, self.ground_deceleration)
+        } else {
+            (self.air_acceleration, self.air_deceleration)
+        };
+
+        let rate = if target == 0. || target.abs() < velocity.abs() {
+            deceleration
+        } else if target * velocity < 0. {
+            acceleration * self.turn_boost
+        } else {
+            acceleration
+        };
+
+        let step = rate * dt;
+        if (target - velocity).abs() <= step {
+            target
+        } else {
+            velocity + step * (target - velocity).signum()
+        }
+    }
+}
+
+/// The player is on a ladder: no gravity, and `LookUp`/`Crouch` move it up
+/// and down.
+#[derive(Reflect, Component, Default, Debug, Clone)]
+pub struct Climbing;
+
+/// The player is spawned once and outlives the floors, so a "Player" placed in
+/// an LDtk level only marks where it starts, see `PlayerStart`.
+#[derive(Bundle)]
 pub struct PlayerBundle {
     health: Health,
     _p: Player,
 
     speed: Speed,
+    facing: Facing,
 
     animation: SpriteAnimation,
     frame_time: FrameTime,
 
     jump: Jump,
-
-    controller: KinematicCharacterController,
-    output: KinematicCharacterControllerOutput,
+    melee: Melee,
 
     #[bundle]
     input_manager: InputManagerBundle<PlayerInput>,
@@ -53,33 +220,36 @@ pub struct PlayerBundle {
     #[bundle]
     sprite: SpriteSheetBundle,
 
-    #[from_entity_instance]
     #[bundle]
-    collision: CollisionBundle,
+    character: CharacterBundle,
 }
 
 #[derive(Component, Reflect, Default, Debug, Clone)]
 pub struct Player;
 
-pub fn spawn_player(mut commands: Commands, animations: Res<PlayerAnimations>) {
+/// Spawns the player at the start of a run. It's kept until the run ends,
+/// see `despawn_player`.
+pub fn spawn_player(
+    mut commands: Commands,
+    animations: Res<PlayerAnimations>,
+    sandbox: Res<Sandbox>,
+    players: Query<(), With<Player>>,
+) {
+    if !players.is_empty() {
+        return;
+    }
+
     let Some((texture_atlas, animation)) = animations.get(Animation::Idle) else { error!("Failed to find animation: Idle"); return;};
 
     let player_bundle = PlayerBundle {
         health: Health::new(4),
         _p: Player,
         speed: Speed(800.),
+        facing: Facing::default(),
         animation,
         frame_time: FrameTime(0.0),
-        jump: Jump(false, 100.),
-        controller: KinematicCharacterController {
-            autostep: Some(CharacterAutostep {
-                max_height: CharacterLength::Relative(0.2),
-                min_width: CharacterLength::Relative(0.0),
-                include_dynamic_bodies: true,
-            }),
-            ..Default::default()
-        },
-        output: KinematicCharacterControllerOutput::default(),
+        jump: Jump::default(),
+        melee: Melee::default(),
         input_manager: InputManagerBundle {
             input_map: PlayerInput::player_one(),
             ..Default::default()
@@ -97,16 +267,13 @@ pub fn spawn_player(mut commands: Commands, animations: Res<PlayerAnimations>) {
             },
             ..SpriteSheetBundle::default()
         },
-        collision: CollisionBundle::new(
-            RigidBody::Dynamic,
-            Collider::cuboid(36., 50.),
-            LockedAxes::ROTATION_LOCKED_Z,
-            Velocity::default(),
-            GravityScale(1.0),
-        ),
+        character: CharacterBundle::player(),
     };
 
-    commands.spawn(player_bundle);
+    let mut player = commands.spawn(player_bundle);
+    if !sandbox.0 {
+        player.insert(AwaitingLevel);
+    }
 }
 
 #[derive(Debug, Actionlike, Clone)]
@@ -119,6 +286,8 @@ pub enum PlayerInput {
     CrouchWalkRight,
     CrouchWalkLeft,
     LookUp,
+    Pause,
+    Attack,
 }
 
 impl PlayerInput {
@@ -130,97 +299,89 @@ impl PlayerInput {
             (KeyCode::Space, PlayerInput::Jump),
             (KeyCode::S, PlayerInput::Crouch),
             (KeyCode::W, PlayerInput::LookUp),
+            (KeyCode::Escape, PlayerInput::Pause),
+            (KeyCode::J, PlayerInput::Attack),
         ]);
+        map.insert(MouseButton::Left, PlayerInput::Attack);
 
         map.insert_chord([KeyCode::S, KeyCode::D], PlayerInput::CrouchWalkRight);
         map.insert_chord([KeyCode::S, KeyCode::A], PlayerInput::CrouchWalkLeft);
+        map.insert_chord([KeyCode::S, KeyCode::Space], PlayerInput::Fall);
 
         map
     }
 }
 
-pub const JUMP_FORCE: f32 = 80.0;
-
 pub fn move_player(
-    mut player: Query<(&mut Velocity, &Speed, &ActionState<PlayerInput>), With<Player>>,
+    mut player: Query<
+        (
+            &mut CharacterVelocity,
+            &mut Facing,
+            &Speed,
+            &Grounded,
+            &ActionState<PlayerInput>,
+        ),
+        With<Player>,
+    >,
+    config: Res<MovementConfig>,
     time: Res<Time>,
 ) {
-    let (mut velocity, speed, input) = player.single_mut();
+    let (mut velocity, mut facing, speed, grounded, input) = player.single_mut();
 
-    if input.pressed(PlayerInput::CrouchWalkLeft) {
-        velocity.linvel.x = -speed.0 * 0.3;
+    let target = if input.pressed(PlayerInput::CrouchWalkLeft) {
+        -config.crouch_speed
     } else if input.pressed(PlayerInput::CrouchWalkRight) {
-        velocity.linvel.x = speed.0 * 0.3;
-    } else if input.just_pressed(PlayerInput::Left) || input.pressed(PlayerInput::Left) {
-        velocity.linvel.x = -speed.0;
-    } else if input.just_pressed(PlayerInput::Right) || input.pressed(PlayerInput::Right) {
-        velocity.linvel.x = speed.0;
-    } else if input.just_released(PlayerInput::Left) {
-        velocity.linvel.x += 30. * input.current_duration(PlayerInput::Left).as_secs_f32();
-    } else if input.just_released(PlayerInput::Right) {
-        velocity.linvel.x -= 3000. * time.delta_seconds();
+        config.crouch_speed
+    } else if input.pressed(PlayerInput::Left) {
+        -speed.0
+    } else if input.pressed(PlayerInput::Right) {
+        speed.0
     } else {
-        velocity.linvel.x = 0.0;
+        0.
+    };
+    if target != 0. {
+        facing.0 = target.signum();
     }
+
+    velocity.linvel.x =
+        config.accelerate(velocity.linvel.x, target, grounded.0, time.delta_seconds());
 }
 
 pub fn jump(
-    input_query: Query<&ActionState<PlayerInput>, With<Player>>,
-    mut controllers: Query<
+    mut players: Query<
         (
-            &mut KinematicCharacterController,
-            &KinematicCharacterControllerOutput,
-            &mut Velocity,
+            &ActionState<PlayerInput>,
+            &Grounded,
+            &mut CharacterVelocity,
             &mut Jump,
         ),
-        With<Player>,
+        (With<Player>, Without<Climbing>),
     >,
-    _commands: Commands,
+    config: Res<JumpConfig>,
     time: Res<Time>,
 ) {
-    for input in input_query.iter() {
-        for (mut controller, k_output, mut velocity, mut jump) in controllers.iter_mut() {
-            match k_output.grounded {
-                true => {
-                    if input.pressed(PlayerInput::Jump) {
-                        velocity.linvel.y += jump.1 * time.delta_seconds();
-                    } else {
-                        controller.translation = match controller.translation {
-                            Some(mut v) => {
-                                v.y = -14.0;
-                                Some(v)
-                            }
-                            None => Some(Vec2::new(0.0, -14.0)),
-                        }
-                    }
-                }
-                false => {
-                    if input.just_pressed(PlayerInput::Jump) && !jump.0 {
-                        velocity.linvel.y += jump.1 * time.delta_seconds() * 1000.;
-                        jump.0 = true;
-                    } else if input.pressed(PlayerInput::Jump)
-                        && input.current_duration(PlayerInput::Jump).as_millis() < 180
-                    {
-                        velocity.linvel.y += 2. * time.delta_seconds() * 1000.;
-                    } else if input.just_released(PlayerInput::Jump) {
-                        velocity.linvel.y -= 5. * time.delta_seconds() * 1000.;
-                    }
-
-                    if velocity.linvel.y == 0.0 {
-                        jump.0 = false;
-                    }
-                }
-            }
-        }
+    for (input, grounded, mut velocity, mut jump) in players.iter_mut() {
+        // Dropping through a platform isn't a jump, see `pass_through_platforms`
+        let pressed = input.just_pressed(PlayerInput::Jump) && !input.pressed(PlayerInput::Fall);
+        velocity.linvel.y = jump.update(
+            &config,
+            grounded.0,
+            pressed,
+            input.pressed(PlayerInput::Jump),
+            time.delta_seconds(),
+            velocity.linvel.y,
+        );
     }
 }
 
 pub fn look_up_down_handle(
-    player: Query<&ActionState<PlayerInput>, With<Player>>,
+    player: Query<&ActionState<PlayerInput>, (With<Player>, Without<Climbing>)>,
     mut camera: Query<&mut Transform, With<CameraTest>>,
     time: Res<Time>,
 ) {
-    let input = player.single();
+    let Ok(input) = player.get_single() else {
+        return;
+    };
     let mut camera = camera.single_mut();
     if input.pressed(PlayerInput::LookUp)
         && input.current_duration(PlayerInput::LookUp).as_secs_f32() > 0.7
@@ -241,26 +402,81 @@ pub fn look_up_down_handle(
     }
 }
 
-pub fn check_borders(
-    mut player: Query<&mut Transform, With<Player>>,
-    mut camera_query: Query<(&CameraTest, &mut Transform), Without<Player>>,
-    window: Query<&Window>,
+pub const CLIMB_SPEED: f32 = 500.0;
+
+/// Grabs a ladder the player touches when `LookUp` or `Crouch` is pressed,
+/// and lets go of it when jumping or leaving it.
+pub fn climb(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut player: Query<
+        (
+            Entity,
+            &mut CharacterVelocity,
+            &mut GravityScale,
+            &mut Jump,
+            &ActionState<PlayerInput>,
+            Option<&Climbing>,
+        ),
+        With<Player>,
+    >,
+    ladders: Query<Entity, With<Climbable>>,
+    config: Res<JumpConfig>,
 ) {
-    let mut controller = player.single_mut();
-    let window = window.single();
+    let Ok((entity, mut velocity, mut gravity, mut jump, input, climbing)) =
+        player.get_single_mut()
+    else {
+        return;
+    };
 
-    if controller.translation.y < -window.height() {
-        let mut camera = camera_query.single_mut();
-        controller.translation.y = window.height();
-        camera.1.translation.y = controller.translation.y;
+    let on_ladder = ladders
+        .iter()
+        .any(|ladder| rapier_context.intersection_pair(ladder, entity) == Some(true));
+
+    if climbing.is_none() {
+        if on_ladder && (input.pressed(PlayerInput::LookUp) || input.pressed(PlayerInput::Crouch)) {
+            commands.entity(entity).insert(Climbing);
+            gravity.0 = 0.0;
+            velocity.linvel.y = 0.0;
+        }
+        return;
+    }
+
+    if !on_ladder || input.just_pressed(PlayerInput::Jump) {
+        commands.entity(entity).remove::<Climbing>();
+        gravity.0 = 1.0;
+        if on_ladder {
+            // Like any other jump, letting go early cuts it short
+            velocity.linvel.y = config.speed;
+            jump.rising = true;
+        }
+        return;
     }
+
+    velocity.linvel.y = if input.pressed(PlayerInput::LookUp) {
+        CLIMB_SPEED
+    } else if input.pressed(PlayerInput::Crouch) {
+        -CLIMB_SPEED
+    } else {
+        0.0
+    };
 }
 
-pub fn check_terminal_velocity(mut player: Query<&mut Velocity, With<Player>>) {
-    let mut controller = player.single_mut();
+/// The run is over when the player dies.
+pub fn game_over_on_death(
+    mut died: EventReader<Died>,
+    player: Query<(), With<Player>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if died.iter().any(|event| player.contains(event.entity)) {
+        info!("The player died");
+        next_state.set(AppState::GameOver);
+    }
+}
 
-    if controller.linvel.y < -3000.0 {
-        controller.linvel.y = -3000.0;
+pub fn despawn_player(mut commands: Commands, players: Query<Entity, With<Player>>) {
+    for player in players.iter() {
+        commands.entity(player).despawn_recursive();
     }
 }
 
@@ -268,16 +484,157 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(spawn_player)
-            .add_system(move_player)
-            .add_system(jump)
-            .add_system(check_borders)
-            .add_system(check_terminal_velocity)
-            .add_system(look_up_down_handle)
+        app.add_system(spawn_player.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(despawn_player.in_schedule(OnExit(AppState::GameOver)))
+            .add_system(despawn_player.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(
+                despawn_player
+                    .run_if(on_event::<RestartRun>())
+                    .in_schedule(OnExit(AppState::Paused)),
+            )
+            .register_ldtk_entity::<PlayerStartBundle>("Player")
+            .add_systems(
+                (update_characters, move_player, jump, climb, move_characters)
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_systems(
+                (look_up_down_handle, game_over_on_death).in_set(OnUpdate(AppState::Playing)),
+            )
+            // The controller's filter has to be up to date before it moves
+            .add_system(
+                pass_through_platforms
+                    .before(update_characters)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .init_resource::<MovementConfig>()
+            .init_resource::<JumpConfig>()
+            .init_resource::<HitStop>()
+            .add_systems((attack, update_hitboxes, hit_stop).in_set(OnUpdate(AppState::Playing)))
+            .add_system(end_hit_stop.in_schedule(OnExit(AppState::Playing)))
+            .register_type::<MovementConfig>()
+            .register_type::<JumpConfig>()
             .register_type::<Jump>()
+            .register_type::<CharacterVelocity>()
+            .register_type::<Grounded>()
+            .register_type::<Climbing>()
+            .register_type::<Melee>()
             .register_type::<Health>()
             .register_type::<GravityScale>()
             .register_type::<Speed>()
+            .register_type::<Facing>()
             .add_plugin(AnimationPlugin);
     }
 }
+
+#[test]
+fn buffered_jumps_fire_on_landing() {
+    let config = JumpConfig::default();
+    for dt in [1. / 30., 1. / 144.] {
+        let mut jump = Jump::default();
+        // Pressed in the air, just before landing
+        assert_eq!(jump.update(&config, false, true, true, dt, -500.), -500.);
+        assert_eq!(
+            jump.update(&config, true, false, true, dt, 0.),
+            config.speed
+        );
+
+        // Pressed too early
+        let mut jump = Jump::default();
+        jump.update(&config, false, true, true, dt, -500.);
+        let mut time = 0.;
+        while time <= config.buffer_time {
+            jump.update(&config, false, false, true, dt, -500.);
+            time += dt;
+        }
+        assert_eq!(jump.update(&config, true, false, true, dt, 0.), 0.);
+    }
+}
+
+#[test]
+fn coyote_time_allows_late_jumps() {
+    let config = JumpConfig::default();
+    for dt in [1. / 30., 1. / 144.] {
+        let mut jump = Jump::default();
+        jump.update(&config, true, false, false, dt, 0.);
+        jump.update(&config, false, false, false, dt, -10.);
+        assert_eq!(
+            jump.update(&config, false, true, true, dt, -20.),
+            config.speed
+        );
+
+        // No second jump in the air
+        assert_eq!(jump.update(&config, false, true, true, dt, 1000.), 1000.);
+
+        let mut jump = Jump::default();
+        jump.update(&config, true, false, false, dt, 0.);
+        let mut time = 0.;
+        while time <= config.coyote_time {
+            jump.update(&config, false, false, false, dt, -10.);
+            time += dt;
+        }
+        assert_eq!(jump.update(&config, false, true, true, dt, -20.), -20.);
+    }
+}
+
+#[test]
+fn released_jumps_are_cut_short() {
+    let config = JumpConfig::default();
+    let mut jump = Jump::default();
+    let dt = 1. / 60.;
+    assert_eq!(jump.update(&config, true, true, true, dt, 0.), config.speed);
+    assert_eq!(jump.update(&config, false, false, true, dt, 1500.), 1500.);
+    assert_eq!(
+        jump.update(&config, false, false, false, dt, 1400.),
+        1400. * config.cut
+    );
+    // Only once
+    assert_eq!(jump.update(&config, false, false, false, dt, 500.), 500.);
+}
+
+#[test]
+fn running_reaches_top_speed_without_overshooting() {
+    let config = MovementConfig::default();
+    for dt in [1. / 30., 1. / 144.] {
+        let mut velocity = 0.;
+        for _ in 0..200 {
+            velocity = config.accelerate(velocity, 800., true, dt);
+            assert!(velocity <= 800.);
+        }
+        assert_eq!(velocity, 800.);
+
+        // Crouching down slows down to the crouch speed, not below
+        for _ in 0..200 {
+            velocity = config.accelerate(velocity, config.crouch_speed, true, dt);
+            assert!(velocity >= config.crouch_speed);
+        }
+        assert_eq!(velocity, config.crouch_speed);
+    }
+}
+
+#[test]
+fn letting_go_slows_down_the_same_both_ways() {
+    let config = MovementConfig::default();
+    let dt = 1. / 60.;
+    for grounded in [true, false] {
+        let (mut right, mut left) = (800., -800.);
+        for _ in 0..5 {
+            right = config.accelerate(right, 0., grounded, dt);
+            left = config.accelerate(left, 0., grounded, dt);
+            assert_eq!(right, -left);
+        }
+        assert!(right > 0. && right < 800.);
+    }
+}
+
+#[test]
+fn turning_around_is_boosted() {
+    let config = MovementConfig::default();
+    let dt = 1. / 60.;
+    let from_standing = config.accelerate(0., 800., true, dt);
+    let turning = config.accelerate(-400., 800., true, dt) + 400.;
+    assert!((turning - from_standing * config.turn_boost).abs() < 1e-3);
+
+    let in_the_air = config.accelerate(0., 800., false, dt);
+    assert!(in_the_air < from_standing);
+}
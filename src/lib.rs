@@ -0,0 +1,85 @@
+use animations::{
+    player_animations::{change_player_animation, PlayerAnimations},
+    sprite_animation::animate_sprite,
+};
+use bevy::prelude::{
+    App, Camera2dBundle, Commands, Component, IntoSystemConfig, OnUpdate, OrthographicProjection,
+    Plugin, Query, Res, Transform, With, Without,
+};
+use entities::{
+    enemies::EnemyPlugin,
+    hazards::HazardPlugin,
+    player::{Player, PlayerPlugin},
+};
+use map::{config::MapConfig, in_sandbox, spawn_map};
+use state::AppState;
+
+pub mod animations;
+pub mod entities;
+pub mod map;
+pub mod menu;
+pub mod state;
+
+pub struct StartupPlugin;
+
+impl Plugin for StartupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_systems((spawn_camera, spawn_map.run_if(in_sandbox)))
+            .add_system(camera_follow_player.in_set(OnUpdate(AppState::Playing)))
+            .add_plugin(PlayerPlugin)
+            .add_plugin(EnemyPlugin)
+            .add_plugin(HazardPlugin);
+    }
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate_sprite.in_set(OnUpdate(AppState::Playing)))
+            .add_system(change_player_animation.in_set(OnUpdate(AppState::Playing)))
+            .init_resource::<PlayerAnimations>();
+    }
+}
+
+#[derive(Component)]
+pub struct CameraTest;
+
+fn spawn_camera(mut commands: Commands) {
+    let mut camera_bundle = Camera2dBundle::default();
+    camera_bundle.projection.scaling_mode = bevy::render::camera::ScalingMode::FixedVertical(1000.);
+    commands.spawn(camera_bundle).insert(CameraTest);
+}
+
+/// This is really rudimentary, but it works for now.
+fn camera_follow_player(
+    query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<
+        (&CameraTest, &mut Transform, &OrthographicProjection),
+        Without<Player>,
+    >,
+    config: Res<MapConfig>,
+) {
+    let player_transform = query.single();
+    let (_, mut camera_transform, projection) = camera_query.single_mut();
+    let direction = camera_transform.translation - player_transform.translation;
+    camera_transform.translation -= direction * 0.20;
+
+    // Keep the view over the map
+    let world_size = config.world_size();
+    let half_view = projection.area.half_size();
+    camera_transform.translation.x =
+        clamp_view(camera_transform.translation.x, half_view.x, world_size.x);
+    camera_transform.translation.y =
+        clamp_view(camera_transform.translation.y, half_view.y, world_size.y);
+}
+
+/// Clamps the center of a view `half_view` wide so that it stays between `0`
+/// and `world_size`, or centers it if the world is smaller than the view.
+fn clamp_view(center: f32, half_view: f32, world_size: f32) -> f32 {
+    if world_size < half_view * 2. {
+        world_size / 2.
+    } else {
+        center.clamp(half_view, world_size - half_view)
+    }
+}
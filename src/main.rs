@@ -1,12 +1,7 @@
-use animations::{
-    player_animations::{change_player_animation, PlayerAnimations},
-    sprite_animation::animate_sprite,
-};
+use std::path::PathBuf;
+
 use bevy::{
-    prelude::{
-        App, Camera2dBundle, Commands, Component, IntoSystemSetConfig, Plugin, Query, Transform,
-        Vec2, With, Without,
-    },
+    prelude::{App, IntoSystemSetConfig, Vec2},
     DefaultPlugins,
 };
 use bevy_ecs_ldtk::{
@@ -19,21 +14,25 @@ use bevy_rapier2d::{
     prelude::{NoUserData, PhysicsSet, RapierConfiguration, RapierPhysicsPlugin},
     render::RapierDebugRenderPlugin,
 };
-use entities::{
-    blocks::WallBundle,
-    player::{Player, PlayerInput, PlayerPlugin},
-};
 use leafwing_input_manager::prelude::InputManagerPlugin;
-use map::{ldtk_setup::LdtkLoader, spawn_map};
-
-pub mod animations;
-pub mod entities;
-pub mod map;
+use roguelike::{
+    entities::{
+        blocks::{LadderBundle, OneWayPlatformBundle, WallCellBundle},
+        hazards::HazardBundle,
+        player::PlayerInput,
+    },
+    map::{ldtk_setup::LdtkLoader, Sandbox},
+    menu::MenuPlugin,
+    state::AppState,
+    StartupPlugin,
+};
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
+        .add_state::<AppState>()
         .add_plugin(StartupPlugin)
+        .add_plugin(MenuPlugin)
         .add_plugin(EditorPlugin::default())
         .add_plugin(InputManagerPlugin::<PlayerInput>::default())
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.))
@@ -47,52 +46,20 @@ fn main() {
             ..Default::default()
         })
         .insert_resource(LevelSelection::Index(0))
+        .insert_resource(Sandbox(std::env::var_os("ROGUELIKE_SANDBOX").is_some()))
         .configure_set(LdtkSystemSet::ProcessApi.before(PhysicsSet::SyncBackend))
         .insert_resource(RapierConfiguration {
             gravity: Vec2::new(0.0, -5000.),
             ..Default::default()
         })
-        .register_ldtk_int_cell::<WallBundle>(1)
-        .add_plugin(LdtkLoader)
+        .register_ldtk_int_cell::<WallCellBundle>(1)
+        .register_ldtk_int_cell::<OneWayPlatformBundle>(2)
+        .register_ldtk_int_cell::<LadderBundle>(3)
+        .register_ldtk_int_cell::<HazardBundle>(4)
+        .register_ldtk_int_cell::<HazardBundle>(5)
+        .add_plugin(LdtkLoader {
+            map_file: std::env::var_os("ROGUELIKE_MAP").map(PathBuf::from),
+            save_map_file: std::env::var_os("ROGUELIKE_SAVE_MAP").map(PathBuf::from),
+        })
         .run()
 }
-
-pub struct StartupPlugin;
-
-impl Plugin for StartupPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_startup_systems((spawn_camera, spawn_map))
-            .add_system(camera_follow_player)
-            .add_plugin(PlayerPlugin);
-    }
-}
-
-pub struct AnimationPlugin;
-
-impl Plugin for AnimationPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_system(animate_sprite)
-            .add_system(change_player_animation)
-            .init_resource::<PlayerAnimations>();
-    }
-}
-
-#[derive(Component)]
-pub struct CameraTest;
-
-fn spawn_camera(mut commands: Commands) {
-    let mut camera_bundle = Camera2dBundle::default();
-    camera_bundle.projection.scaling_mode = bevy::render::camera::ScalingMode::FixedVertical(1000.);
-    commands.spawn(camera_bundle).insert(CameraTest);
-}
-
-/// This is really rudimentary, but it works for now.
-fn camera_follow_player(
-    query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<(&CameraTest, &mut Transform), Without<Player>>,
-) {
-    let player_transform = query.single();
-    let (_, mut camera_transform) = camera_query.single_mut();
-    let direction = camera_transform.translation - player_transform.translation;
-    camera_transform.translation -= direction * 0.20;
-}
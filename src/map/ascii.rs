@@ -0,0 +1,193 @@
+//! Text form of a `Map`, for tests, debugging and hand-authored layouts.
+//!
+//! Every row of the map is a line, and every tile a whitespace separated
+//! token built from these glyphs:
+//!
+//! | Token  | Tile                                                        |
+//! |--------|-------------------------------------------------------------|
+//! | `S>`   | entrance, leaving through the given side                    |
+//! | `E^`   | exit, entered from the given side                           |
+//! | `^v`   | path room, entered from the first side, left by the second  |
+//! | `.`    | closed side room                                            |
+//! | `.<>`  | side room open on the given sides                           |
+//!
+//! Sides are written `^` (up), `v` (down), `>` (right) and `<` (left), so
+//! the 3x3 map
+//!
+//! ```text
+//! .   Sv  .
+//! .v  ^>  <v
+//! .^  .   E^
+//! ```
+//!
+//! starts at the top, goes right and then down into the exit.
+
+use std::fmt::{Display, Formatter};
+
+use super::generator::{Direction, Map, MapTile, Openings};
+
+impl Direction {
+    pub fn glyph(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Right => '>',
+            Direction::Left => '<',
+        }
+    }
+
+    pub fn from_glyph(glyph: char) -> Option<Self> {
+        match glyph {
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            '>' => Some(Direction::Right),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the tile's token, see the module docs.
+impl Display for MapTile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapTile::Entrance { to, .. } => write!(f, "S{}", to.glyph()),
+            MapTile::Exit { from, .. } => write!(f, "E{}", from.glyph()),
+            MapTile::Path { from, to, .. } => write!(f, "{}{}", from.glyph(), to.glyph()),
+            MapTile::Empty { openings, .. } => {
+                write!(f, ".")?;
+                for dir in openings.iter() {
+                    write!(f, "{}", dir.glyph())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_tile(token: &str, pos: (u32, u32)) -> Result<MapTile, String> {
+    let mut chars = token.chars();
+    let kind = chars.next();
+    let sides = chars
+        .map(|glyph| {
+            Direction::from_glyph(glyph)
+                .ok_or_else(|| format!("Unknown side '{}' in {:?} at {:?}", glyph, token, pos))
+        })
+        .collect::<Result<Vec<_>, _>>();
+
+    match (kind, sides?.as_slice()) {
+        (Some('S'), [to]) => Ok(MapTile::Entrance { pos, to: *to }),
+        (Some('E'), [from]) => Ok(MapTile::Exit { pos, from: *from }),
+        (Some('.'), sides) => Ok(MapTile::Empty {
+            pos,
+            openings: sides
+                .iter()
+                .fold(Openings::CLOSED, |openings, dir| openings.with(*dir)),
+        }),
+        (Some(from), [to]) => match Direction::from_glyph(from) {
+            Some(from) => Ok(MapTile::Path { pos, from, to: *to }),
+            None => Err(format!("Unknown tile {:?} at {:?}", token, pos)),
+        },
+        _ => Err(format!("Unknown tile {:?} at {:?}", token, pos)),
+    }
+}
+
+impl Map {
+    /// Renders the map in the format described in the module docs.
+    pub fn to_ascii(&self) -> String {
+        let tokens: Vec<String> = self.map_tiles.iter().map(ToString::to_string).collect();
+        let pad = tokens.iter().map(String::len).max().unwrap_or(0);
+
+        let mut ascii = String::new();
+        for row in tokens.chunks(self.width as usize) {
+            let tokens: Vec<String> = row.iter().map(|token| format!("{:<pad$}", token)).collect();
+            ascii.push_str(tokens.join(" ").trim_end());
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// Parses a map written in the format described in the module docs.
+    /// Blank lines are ignored. The map isn't validated, see `Map::validate`.
+    pub fn from_ascii(ascii: &str) -> Result<Self, String> {
+        let mut map_tiles = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for line in ascii.lines().filter(|line| !line.trim().is_empty()) {
+            let row = line
+                .split_whitespace()
+                .enumerate()
+                .map(|(x, token)| parse_tile(token, (x as u32, height)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            match width {
+                None => width = Some(row.len() as u32),
+                Some(width) if width != row.len() as u32 => {
+                    return Err(format!(
+                        "Row {} has {} tiles, expected {}",
+                        height,
+                        row.len(),
+                        width
+                    ));
+                }
+                Some(_) => {}
+            }
+            map_tiles.extend(row);
+            height += 1;
+        }
+
+        let find =
+            |is: fn(&MapTile) -> bool| map_tiles.iter().find(|tile| is(tile)).map(MapTile::pos);
+        let start = find(|tile| matches!(tile, MapTile::Entrance { .. })).ok_or("No entrance")?;
+        let end = find(|tile| matches!(tile, MapTile::Exit { .. })).ok_or("No exit")?;
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            start,
+            end,
+            map_tiles,
+        })
+    }
+}
+
+#[test]
+fn ascii_round_trip() {
+    use super::generator::seeded_map;
+
+    for seed in 0..200 {
+        let map = seeded_map(7, 5, seed, |_| true);
+
+        let parsed = Map::from_ascii(&map.to_ascii()).unwrap();
+        assert_eq!(parsed.width, map.width);
+        assert_eq!(parsed.height, map.height);
+        assert_eq!(parsed.start, map.start);
+        assert_eq!(parsed.end, map.end);
+        assert!(parsed.map_tiles == map.map_tiles);
+    }
+}
+
+#[test]
+fn hand_authored_map() {
+    let map = Map::from_ascii(
+        "
+        .   Sv  .
+        .v  ^>  <v
+        .^  .   E^
+        ",
+    )
+    .unwrap();
+
+    assert_eq!((map.width, map.height), (3, 3));
+    assert_eq!((map.start, map.end), ((1, 0), (2, 2)));
+    assert!(map.validate().is_ok());
+}
+
+/// Golden file for the generator. If a change to the generator is intended,
+/// regenerate it with the output of `Map::with_seed(8, 6, 42).to_ascii()`.
+#[test]
+fn generator_golden_file() {
+    let map = Map::with_seed(8, 6, 42);
+    assert_eq!(map.to_ascii(), include_str!("golden/seed_42.txt"));
+}
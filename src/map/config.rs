@@ -0,0 +1,57 @@
+use bevy::prelude::{FromWorld, Resource, Vec2, World};
+
+use super::templates::RoomTemplateRegistry;
+
+/// Size and scale of the generated map.
+#[derive(Resource, Debug, Clone)]
+pub struct MapConfig {
+    /// Number of rooms per row.
+    pub width: u32,
+    /// Number of rooms per column.
+    pub height: u32,
+    /// Size of a room in LDtk pixels.
+    pub room_size: Vec2,
+    /// Scale every room is spawned with.
+    pub scale: f32,
+    /// Every this many floors the map grows by one room in each direction,
+    /// `0` keeps the size fixed.
+    pub growth_interval: u32,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            width: 3,
+            height: 3,
+            room_size: Vec2::splat(1920.),
+            scale: 0.7,
+            growth_interval: 2,
+        }
+    }
+}
+
+/// Takes the room size from the room templates, if they are loaded.
+impl FromWorld for MapConfig {
+    fn from_world(world: &mut World) -> Self {
+        let mut config = MapConfig::default();
+        if let Some((width, height)) = world
+            .get_resource::<RoomTemplateRegistry>()
+            .and_then(|registry| registry.room_size())
+        {
+            config.room_size = Vec2::new(width as f32, height as f32);
+        }
+        config
+    }
+}
+
+impl MapConfig {
+    /// Size of a room in world units.
+    pub fn room_world_size(&self) -> Vec2 {
+        self.room_size * self.scale
+    }
+
+    /// Size of the whole map in world units.
+    pub fn world_size(&self) -> Vec2 {
+        self.room_world_size() * Vec2::new(self.width as f32, self.height as f32)
+    }
+}
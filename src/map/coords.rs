@@ -0,0 +1,79 @@
+//! Conversions between map grid positions and world positions.
+//!
+//! Row 0 of the `Map` is the top of the world, so going down the grid goes
+//! down in Bevy's y-up world. The map covers the rectangle from the origin
+//! to `MapConfig::world_size`.
+
+use bevy::prelude::Vec2;
+
+use super::config::MapConfig;
+
+impl MapConfig {
+    /// World position of the bottom-left corner of the room at `pos`, which
+    /// is where its level is spawned.
+    pub fn grid_to_world(&self, pos: (u32, u32)) -> Vec2 {
+        let room_size = self.room_world_size();
+        Vec2::new(
+            pos.0 as f32 * room_size.x,
+            (self.height - 1 - pos.1) as f32 * room_size.y,
+        )
+    }
+
+    /// World position of the center of the room at `pos`.
+    pub fn room_center(&self, pos: (u32, u32)) -> Vec2 {
+        let corner = self.grid_to_world(pos);
+        let room_size = self.room_world_size();
+        Vec2::new(corner.x + room_size.x / 2., corner.y + room_size.y / 2.)
+    }
+
+    /// Grid position of the room containing `world`, if it is on the map.
+    pub fn world_to_grid(&self, world: Vec2) -> Option<(u32, u32)> {
+        let room_size = self.room_world_size();
+        let column = (world.x / room_size.x).floor();
+        let row_from_bottom = (world.y / room_size.y).floor();
+
+        if column < 0.
+            || row_from_bottom < 0.
+            || column >= self.width as f32
+            || row_from_bottom >= self.height as f32
+        {
+            return None;
+        }
+        Some((column as u32, self.height - 1 - row_from_bottom as u32))
+    }
+}
+
+#[test]
+fn grid_world_round_trip() {
+    let config = MapConfig {
+        width: 4,
+        height: 3,
+        ..Default::default()
+    };
+
+    for y in 0..config.height {
+        for x in 0..config.width {
+            let corner = config.grid_to_world((x, y));
+            let center = config.room_center((x, y));
+            assert_eq!(config.world_to_grid(corner), Some((x, y)));
+            assert_eq!(config.world_to_grid(center), Some((x, y)));
+        }
+    }
+}
+
+#[test]
+fn row_zero_is_at_the_top() {
+    let config = MapConfig {
+        width: 2,
+        height: 3,
+        room_size: Vec2::splat(100.),
+        scale: 0.5,
+        ..Default::default()
+    };
+
+    assert_eq!(config.grid_to_world((0, 0)), Vec2::new(0., 100.));
+    assert_eq!(config.grid_to_world((1, 2)), Vec2::new(50., 0.));
+    assert!(config.room_center((0, 0)).y > config.room_center((0, 1)).y);
+    assert_eq!(config.world_to_grid(Vec2::new(-1., 10.)), None);
+    assert_eq!(config.world_to_grid(Vec2::new(10., 150.)), None);
+}
@@ -0,0 +1,132 @@
+use std::{
+    fmt::{Display, Formatter},
+    fs, io,
+    path::Path,
+};
+
+use super::generator::Map;
+
+/// Error reading or writing a map file. The format is picked from the
+/// extension, `.ron` or `.json`.
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(io::Error),
+    /// RON that is not a valid map, with the line and column of the problem.
+    RonParse(ron::error::SpannedError),
+    Ron(ron::Error),
+    Json(serde_json::Error),
+    UnknownFormat(String),
+}
+
+impl Display for MapFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapFileError::Io(e) => write!(f, "{}", e),
+            MapFileError::RonParse(e) => write!(f, "invalid RON at {}: {}", e.position, e.code),
+            MapFileError::Ron(e) => write!(f, "invalid RON: {}", e),
+            MapFileError::Json(e) => write!(f, "invalid JSON: {}", e),
+            MapFileError::UnknownFormat(path) => {
+                write!(f, "{} is neither a .ron nor a .json file", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapFileError {}
+
+impl From<io::Error> for MapFileError {
+    fn from(e: io::Error) -> Self {
+        MapFileError::Io(e)
+    }
+}
+
+impl From<ron::Error> for MapFileError {
+    fn from(e: ron::Error) -> Self {
+        MapFileError::Ron(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for MapFileError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        MapFileError::RonParse(e)
+    }
+}
+
+impl From<serde_json::Error> for MapFileError {
+    fn from(e: serde_json::Error) -> Self {
+        MapFileError::Json(e)
+    }
+}
+
+enum Format {
+    Ron,
+    Json,
+}
+
+impl Format {
+    fn of(path: &Path) -> Result<Self, MapFileError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Ok(Format::Ron),
+            Some("json") => Ok(Format::Json),
+            _ => Err(MapFileError::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+impl Map {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapFileError> {
+        let path = path.as_ref();
+        let format = Format::of(path)?;
+        let contents = fs::read_to_string(path)?;
+
+        Ok(match format {
+            Format::Ron => ron::from_str(&contents)?,
+            Format::Json => serde_json::from_str(&contents)?,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MapFileError> {
+        let path = path.as_ref();
+        let contents = match Format::of(path)? {
+            Format::Ron => ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?,
+            Format::Json => serde_json::to_string_pretty(self)?,
+        };
+
+        Ok(fs::write(path, contents)?)
+    }
+}
+
+#[test]
+fn save_and_load() {
+    use super::generator::seeded_map;
+
+    let map = seeded_map(5, 5, 3, |_| true);
+
+    for ext in ["ron", "json"] {
+        let path = std::env::temp_dir().join(format!("roguelike_map_test.{}", ext));
+        map.save(&path).unwrap();
+        let loaded = Map::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.to_ascii(), map.to_ascii());
+        assert!(loaded.map_tiles == map.map_tiles);
+    }
+
+    assert!(matches!(
+        map.save("map.txt"),
+        Err(MapFileError::UnknownFormat(_))
+    ));
+}
+
+#[test]
+fn parse_errors_have_a_position() {
+    let path = std::env::temp_dir().join("roguelike_malformed_map_test.ron");
+    fs::write(&path, "(\n    width: 5,\n    height: five,\n)").unwrap();
+    let result = Map::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    match result {
+        Err(MapFileError::RonParse(e)) => assert_eq!(e.position.line, 3),
+        result => panic!("expected a RON parse error, got {:?}", result),
+    }
+}
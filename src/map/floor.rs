@@ -0,0 +1,152 @@
+use bevy::prelude::{
+    info, AssetServer, Bundle, Commands, Component, DespawnRecursiveExt, Entity, EventReader,
+    EventWriter, Query, Res, ResMut, Resource, Transform, TransformBundle, With, Without,
+};
+use bevy_rapier2d::prelude::{Collider, RapierContext, Sensor};
+
+use crate::entities::{hazards::KillVolume, player::Player};
+
+use super::{
+    config::MapConfig,
+    ldtk_setup::{floor_rng, generate_map, spawn_floor, MapSeed},
+    spawn::AwaitingLevel,
+    templates::RoomTemplateRegistry,
+};
+
+/// Depth of the current floor, `0` for the first one.
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct FloorNumber(pub u32);
+
+/// Everything that belongs to the current floor and goes away with it.
+#[derive(Component, Default, Debug, Clone)]
+pub struct FloorEntity;
+
+#[derive(Component, Default, Debug, Clone)]
+pub struct ExitSensor;
+
+/// Sent when the player touches the `ExitSensor`.
+pub struct FloorCleared;
+
+#[derive(Bundle)]
+pub struct ExitSensorBundle {
+    _e: ExitSensor,
+    _f: FloorEntity,
+    collider: Collider,
+    sensor: Sensor,
+
+    #[bundle]
+    transform: TransformBundle,
+}
+
+impl ExitSensorBundle {
+    /// A sensor in the middle of the room at `pos`, an eighth of the room wide.
+    pub fn new(config: &MapConfig, pos: (u32, u32)) -> Self {
+        let half_size = config.room_world_size() / 16.;
+        Self {
+            _e: ExitSensor,
+            _f: FloorEntity,
+            collider: Collider::cuboid(half_size.x, half_size.y),
+            sensor: Sensor,
+            transform: TransformBundle::from_transform(Transform::from_translation(
+                config.room_center(pos).extend(0.),
+            )),
+        }
+    }
+}
+
+/// A kill volume spanning the map, half a room below it.
+#[derive(Bundle)]
+pub struct KillVolumeBundle {
+    _k: KillVolume,
+    _f: FloorEntity,
+    collider: Collider,
+    sensor: Sensor,
+
+    #[bundle]
+    transform: TransformBundle,
+}
+
+impl KillVolumeBundle {
+    pub fn new(config: &MapConfig) -> Self {
+        let room_size = config.room_world_size();
+        let world_size = config.world_size();
+        Self {
+            _k: KillVolume,
+            _f: FloorEntity,
+            collider: Collider::cuboid(world_size.x / 2. + room_size.x, room_size.y / 2.),
+            sensor: Sensor,
+            transform: TransformBundle::from_transform(Transform::from_xyz(
+                world_size.x / 2.,
+                -room_size.y,
+                0.,
+            )),
+        }
+    }
+}
+
+/// Ignores a player `AwaitingLevel`, it still stands where the last floor's
+/// exit was.
+pub fn check_exit(
+    rapier_context: Res<RapierContext>,
+    player: Query<Entity, (With<Player>, Without<AwaitingLevel>)>,
+    exits: Query<Entity, With<ExitSensor>>,
+    mut cleared: EventWriter<FloorCleared>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    if exits
+        .iter()
+        .any(|exit| rapier_context.intersection_pair(exit, player) == Some(true))
+    {
+        cleared.send(FloorCleared);
+    }
+}
+
+/// Replaces the current floor with a new one, one step deeper, and sends the
+/// player to its entrance. The player entity itself is kept, and with it
+/// everything it carries.
+#[allow(clippy::too_many_arguments)]
+pub fn next_floor(
+    mut cleared: EventReader<FloorCleared>,
+    mut commands: Commands,
+    floor_entities: Query<Entity, With<FloorEntity>>,
+    player: Query<Entity, With<Player>>,
+    mut floor: ResMut<FloorNumber>,
+    seed: Res<MapSeed>,
+    asset_server: Res<AssetServer>,
+    registry: Res<RoomTemplateRegistry>,
+    mut config: ResMut<MapConfig>,
+) {
+    if cleared.iter().last().is_none() {
+        return;
+    }
+
+    for entity in floor_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    floor.0 += 1;
+    if config.growth_interval > 0 && floor.0 % config.growth_interval == 0 {
+        config.width += 1;
+        config.height += 1;
+    }
+    info!("Generating floor {}", floor.0);
+
+    let mut rng = floor_rng(seed.0, floor.0);
+    let map = generate_map(&config, &registry, &mut rng);
+    spawn_floor(
+        &mut commands,
+        &asset_server,
+        &registry,
+        &mut config,
+        map,
+        floor.0,
+        &mut rng,
+    );
+
+    for player in player.iter() {
+        commands.entity(player).insert(AwaitingLevel);
+    }
+}
@@ -1,15 +1,49 @@
-use std::fmt::{Debug, Display, Formatter};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A side of a room. Rooms on the main path are described by the side the
+/// path enters from and the side it leaves through, so a room entered from
+/// above has `from: Direction::Up`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
+    Up,
     Down,
     Right,
     Left,
 }
 
+impl Direction {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
+        }
+    }
+
+    /// The neighbouring grid position on this side of `pos`, if it is inside
+    /// a `width` x `height` grid. Row 0 is the top of the map.
+    pub fn step(&self, pos: (u32, u32), width: u32, height: u32) -> Option<(u32, u32)> {
+        let (x, y) = pos;
+        match self {
+            Direction::Up => y.checked_sub(1).map(|y| (x, y)),
+            Direction::Down => (y + 1 < height).then_some((x, y + 1)),
+            Direction::Right => (x + 1 < width).then_some((x + 1, y)),
+            Direction::Left => x.checked_sub(1).map(|x| (x, y)),
+        }
+    }
+}
+
 impl Display for Direction {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Direction::Up => write!(f, "up"),
             Direction::Down => write!(f, "down"),
             Direction::Right => write!(f, "right"),
             Direction::Left => write!(f, "left"),
@@ -17,7 +51,108 @@ impl Display for Direction {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "right" => Ok(Direction::Right),
+            "left" => Ok(Direction::Left),
+            _ => Err(format!("Unknown direction: {}", s)),
+        }
+    }
+}
+
+/// Set of sides a room is open on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Openings(u8);
+
+impl Openings {
+    pub const CLOSED: Openings = Openings(0);
+
+    fn bit(dir: Direction) -> u8 {
+        match dir {
+            Direction::Up => 1,
+            Direction::Down => 1 << 1,
+            Direction::Right => 1 << 2,
+            Direction::Left => 1 << 3,
+        }
+    }
+
+    pub fn with(self, dir: Direction) -> Self {
+        Self(self.0 | Self::bit(dir))
+    }
+
+    pub fn contains(&self, dir: Direction) -> bool {
+        self.0 & Self::bit(dir) != 0
+    }
+
+    pub fn is_subset(&self, other: Openings) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Direction> + '_ {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Right,
+            Direction::Left,
+        ]
+        .into_iter()
+        .filter(|dir| self.contains(*dir))
+    }
+}
+
+/// `closed`, or the open sides joined by `-`, e.g. `right-left`.
+impl Display for Openings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "closed");
+        }
+        let sides: Vec<String> = self.iter().map(|dir| dir.to_string()).collect();
+        write!(f, "{}", sides.join("-"))
+    }
+}
+
+/// Stored as its `Display` form, e.g. `"right-left"`.
+impl Serialize for Openings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Openings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl FromStr for Openings {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "closed" {
+            return Ok(Openings::CLOSED);
+        }
+        s.split('-').try_fold(Openings::CLOSED, |openings, dir| {
+            Ok(openings.with(dir.parse()?))
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum MapTile {
     Entrance {
         pos: (u32, u32),
@@ -32,18 +167,21 @@ pub enum MapTile {
         from: Direction,
         to: Direction,
     },
+    /// A side room off the main path. Its openings only ever lead into
+    /// other side rooms, never into path rooms or off the map.
     Empty {
         pos: (u32, u32),
+        openings: Openings,
     },
 }
 
-impl Display for MapTile {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl MapTile {
+    pub fn pos(&self) -> (u32, u32) {
         match self {
-            Self::Entrance { .. } => write!(f, "S"),
-            Self::Exit { .. } => write!(f, "E"),
-            Self::Path { to, .. } => write!(f, "{}", to),
-            Self::Empty { .. } => write!(f, ""),
+            MapTile::Entrance { pos, .. }
+            | MapTile::Exit { pos, .. }
+            | MapTile::Path { pos, .. }
+            | MapTile::Empty { pos, .. } => *pos,
         }
     }
 }
@@ -60,14 +198,14 @@ impl Debug for MapTile {
             MapTile::Path { pos, from, to } => {
                 write!(f, "[Path: {:?}; from: {:?}, to: {:?}]", pos, from, to)
             }
-            MapTile::Empty { pos } => {
-                write!(f, "[Empty: {:?}]", pos)
+            MapTile::Empty { pos, openings } => {
+                write!(f, "[Empty: {:?}; openings: {}]", pos, openings)
             }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Map {
     pub width: u32,
     pub height: u32,
@@ -77,136 +215,232 @@ pub struct Map {
 }
 
 impl Map {
+    /// Generates a map from a random seed.
     pub fn new(width: u32, height: u32) -> Self {
-        let start = (rand::random::<u32>() % width, 0);
-        let (end, map_tiles) = generate_main_path(width, height, start);
+        Self::with_seed(width, height, rand::random())
+    }
+
+    /// Generates a map from `seed`. The same seed always gives the same layout.
+    pub fn with_seed(width: u32, height: u32, seed: u64) -> Self {
+        Self::with_rng(width, height, &mut StdRng::seed_from_u64(seed))
+    }
 
-        Self {
+    /// Generates a map drawing every random choice from `rng`.
+    pub fn with_rng<R: Rng + ?Sized>(width: u32, height: u32, rng: &mut R) -> Self {
+        assert!(
+            width >= 1 && height >= 2,
+            "A map needs at least one column and two rows"
+        );
+
+        let start = (rng.gen_range(0..width), 0);
+        let (end, map_tiles) = generate_main_path(width, height, start, rng);
+
+        let map = Self {
             width,
             height,
             start,
             end,
             map_tiles,
-        }
+        };
+        map.debug_validate();
+        map
     }
 
     pub fn add_path(&mut self, map_tiles: Vec<MapTile>) {
         self.map_tiles = map_tiles;
     }
+
+    pub fn tile(&self, pos: (u32, u32)) -> Option<&MapTile> {
+        if pos.0 >= self.width || pos.1 >= self.height {
+            return None;
+        }
+        self.map_tiles.get((pos.1 * self.width + pos.0) as usize)
+    }
+
+    /// Randomly opens walls between neighbouring side rooms, so they form
+    /// small areas and loops off the main path. An edge is only opened if
+    /// `available` accepts the resulting openings of both rooms, which lets
+    /// the caller restrict the layout to the side room templates it has.
+    pub fn connect_side_rooms<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        available: impl Fn(Openings) -> bool,
+    ) {
+        for i in 0..self.map_tiles.len() {
+            for dir in [Direction::Right, Direction::Down] {
+                let MapTile::Empty { pos, openings } = self.map_tiles[i] else {
+                    break;
+                };
+                let Some(next_pos) = dir.step(pos, self.width, self.height) else {
+                    continue;
+                };
+                let j = (next_pos.1 * self.width + next_pos.0) as usize;
+                let MapTile::Empty {
+                    openings: next_openings,
+                    ..
+                } = self.map_tiles[j]
+                else {
+                    continue;
+                };
+
+                let openings = openings.with(dir);
+                let next_openings = next_openings.with(dir.opposite());
+                if rng.gen_bool(0.5) && available(openings) && available(next_openings) {
+                    self.map_tiles[i] = MapTile::Empty { pos, openings };
+                    self.map_tiles[j] = MapTile::Empty {
+                        pos: next_pos,
+                        openings: next_openings,
+                    };
+                }
+            }
+        }
+
+        self.debug_validate();
+    }
 }
 
-pub fn generate_main_path(
+pub fn generate_main_path<R: Rng + ?Sized>(
     width: u32,
     height: u32,
     start: (u32, u32),
+    rng: &mut R,
 ) -> ((u32, u32), Vec<MapTile>) {
     let mut current_pos = start;
-    let mut to_save_pos = start;
+    // Side through which the path entered the current room, `None` in the entrance.
+    let mut from: Option<Direction> = None;
 
-    let mut prev_dir = Direction::Down;
     let mut map_tiles = Vec::<MapTile>::new();
     let mut direction_map_tiles = Vec::<MapTile>::new();
 
-    let mut first = true;
-
     while current_pos.1 != height - 1 {
-        let direction: Option<Direction> = match rand::random::<u32>() % 20 {
-            0..=2 => Some(Direction::Down),
-            3..=12 => {
-                if current_pos.0 != width - 1 && prev_dir != Direction::Left {
-                    current_pos.0 += 1;
-                    Some(Direction::Right)
-                } else {
-                    None
-                }
-            }
-            13..=20 => {
-                if current_pos.0 != 0 && prev_dir != Direction::Right {
-                    current_pos.0 -= 1;
-                    Some(Direction::Left)
-                } else {
-                    None
-                }
-            }
+        let dir = match rng.gen_range(0..20) {
+            0..=2 => Direction::Down,
+            3..=12 => Direction::Right,
+            13..=19 => Direction::Left,
             _ => panic!("This should never happen"),
         };
 
-        if let Some(dir) = direction {
-            let map_tile: MapTile;
-            if to_save_pos.1 == 0 && first {
-                map_tile = MapTile::Entrance {
-                    pos: start,
-                    to: dir,
-                };
-                first = false;
-            } else {
-                map_tile = MapTile::Path {
-                    pos: to_save_pos,
-                    from: prev_dir,
-                    to: dir,
-                };
-            }
-            direction_map_tiles.push(map_tile);
-            if dir == Direction::Down {
-                current_pos.1 += 1;
-            }
-
-            prev_dir = dir;
-            to_save_pos = current_pos;
+        // Never leave through the side we came in from, nor off the grid.
+        if Some(dir) == from {
+            continue;
         }
+        let Some(next_pos) = dir.step(current_pos, width, height) else {
+            continue;
+        };
+
+        let map_tile = match from {
+            None => MapTile::Entrance {
+                pos: current_pos,
+                to: dir,
+            },
+            Some(from) => MapTile::Path {
+                pos: current_pos,
+                from,
+                to: dir,
+            },
+        };
+        direction_map_tiles.push(map_tile);
+
+        from = Some(dir.opposite());
+        current_pos = next_pos;
     }
 
     direction_map_tiles.push(MapTile::Exit {
         pos: current_pos,
-        from: prev_dir,
+        from: from.unwrap_or(Direction::Up),
     });
 
     for i in 0..height {
         for j in 0..width {
-            map_tiles.push(MapTile::Empty { pos: (j, i) });
+            map_tiles.push(MapTile::Empty {
+                pos: (j, i),
+                openings: Openings::CLOSED,
+            });
         }
     }
 
-    for dir_map_tile in direction_map_tiles.iter() {
-        match dir_map_tile {
-            MapTile::Path { pos, from, to } => {
-                let (x, y) = pos;
-                map_tiles[(y * width + x) as usize] = MapTile::Path {
-                    pos: (*x, *y),
-                    from: *from,
-                    to: *to,
-                };
-            }
-            MapTile::Entrance { pos, to } => {
-                let (x, y) = pos;
-                map_tiles[(y * width + x) as usize] = MapTile::Entrance {
-                    pos: (*x, *y),
-                    to: *to,
-                };
-            }
-            MapTile::Exit { pos, from } => {
-                let (x, y) = pos;
-                map_tiles[(y * width + x) as usize] = MapTile::Exit {
-                    pos: (*x, *y),
-                    from: *from,
-                };
-            }
-            _ => {
-                panic!("No Empty tiles should be here");
-            }
-        }
+    for dir_map_tile in direction_map_tiles {
+        let (x, y) = dir_map_tile.pos();
+        map_tiles[(y * width + x) as usize] = dir_map_tile;
     }
 
-    ((current_pos.0, current_pos.1), map_tiles)
+    (current_pos, map_tiles)
+}
+
+/// The map `seed` gives, with its side rooms connected wherever `available`
+/// allows it.
+#[cfg(test)]
+pub(crate) fn seeded_map(
+    width: u32,
+    height: u32,
+    seed: u64,
+    available: impl Fn(Openings) -> bool,
+) -> Map {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut map = Map::with_rng(width, height, &mut rng);
+    map.connect_side_rooms(&mut rng, available);
+    map
 }
 
 #[test]
 fn generate_map() {
     let map = Map::new(10, 10);
-    for i in 0..map.height {
-        for j in 0..map.width {
-            print!(" {} ", map.map_tiles[(i * map.width + j) as usize]);
+    println!("{}", map.to_ascii());
+}
+
+#[test]
+fn same_seed_same_map() {
+    let a = Map::with_seed(10, 10, 42);
+    let b = Map::with_seed(10, 10, 42);
+    assert_eq!(a.start, b.start);
+    assert_eq!(a.end, b.end);
+    assert!(a.map_tiles == b.map_tiles);
+}
+
+#[test]
+fn side_rooms_keep_main_path_traversable() {
+    for seed in 0..1000 {
+        let map = seeded_map(6, 6, seed, |_| true);
+
+        // Follow the main path from the entrance to the exit.
+        let mut pos = map.start;
+        let mut steps = 0;
+        loop {
+            let to = match map.tile(pos) {
+                Some(MapTile::Entrance { to, .. }) | Some(MapTile::Path { to, .. }) => *to,
+                Some(MapTile::Exit { .. }) => break,
+                tile => panic!("seed {}: main path broken at {:?}: {:?}", seed, pos, tile),
+            };
+            pos = to.step(pos, map.width, map.height).unwrap();
+            match map.tile(pos) {
+                Some(MapTile::Path { from, .. }) | Some(MapTile::Exit { from, .. }) => {
+                    assert_eq!(*from, to.opposite(), "seed {}: at {:?}", seed, pos)
+                }
+                tile => panic!("seed {}: main path broken at {:?}: {:?}", seed, pos, tile),
+            }
+            steps += 1;
+            assert!(
+                steps < map.map_tiles.len(),
+                "seed {}: main path loops",
+                seed
+            );
+        }
+        assert_eq!(pos, map.end);
+
+        // Side rooms only open into side rooms that open back.
+        for tile in map.map_tiles.iter() {
+            let MapTile::Empty { pos, openings } = tile else {
+                continue;
+            };
+            for dir in openings.iter() {
+                let next = dir.step(*pos, map.width, map.height);
+                match next.and_then(|next| map.tile(next)) {
+                    Some(MapTile::Empty { openings, .. }) => {
+                        assert!(openings.contains(dir.opposite()))
+                    }
+                    tile => panic!("seed {}: {:?} opens into {:?}", seed, pos, tile),
+                }
+            }
         }
-        println!();
     }
 }
@@ -1,82 +1,257 @@
-use std::{env::current_dir, fmt::format, ops::Mul};
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+};
 
-use bevy::prelude::{App, AssetServer, Commands, Plugin, Res, Transform, Vec3};
-use bevy_ecs_ldtk::LdtkWorldBundle;
+use bevy::prelude::{
+    error, info, on_event, App, AssetServer, Commands, DespawnRecursiveExt, DetectChanges, Entity,
+    IntoSystemAppConfig, IntoSystemConfig, IntoSystemConfigs, OnEnter, OnExit, OnUpdate, Plugin,
+    Query, Res, ResMut, Resource, Transform, Vec3, With,
+};
+use bevy_ecs_ldtk::{prelude::LdtkEntityAppExt, LdtkWorldBundle};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use super::generator::{Map, MapTile};
+use crate::state::{AppState, RestartRun};
 
-pub struct LdtkLoader;
+use super::{
+    config::MapConfig,
+    floor::{
+        check_exit, next_floor, ExitSensorBundle, FloorCleared, FloorEntity, FloorNumber,
+        KillVolumeBundle,
+    },
+    generator::Map,
+    spawn::{place_player, EntranceRoom, PlayerStartBundle},
+    templates::{RoomKind, RoomTemplateRegistry},
+    walls::spawn_wall_colliders,
+};
+
+/// Seed of the current run. Set `ROGUELIKE_SEED` to replay a layout.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct MapSeed(pub u64);
+
+impl Default for MapSeed {
+    fn default() -> Self {
+        let seed = std::env::var("ROGUELIKE_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(rand::random);
+        Self(seed)
+    }
+}
+
+/// Map file loaded instead of generating a map, see `LdtkLoader::map_file`.
+#[derive(Resource, Debug, Clone)]
+pub struct MapFile(pub PathBuf);
+
+/// Where the map of every floor is saved, see `LdtkLoader::save_map_file`.
+#[derive(Resource, Debug, Clone)]
+pub struct SaveMapFile(pub PathBuf);
+
+#[derive(Default)]
+pub struct LdtkLoader {
+    /// Load this `.ron` or `.json` map instead of generating one.
+    pub map_file: Option<PathBuf>,
+    /// Save the map of every floor to this `.ron` or `.json` file, e.g. to
+    /// attach it to a bug report.
+    pub save_map_file: Option<PathBuf>,
+}
 
 impl Plugin for LdtkLoader {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup);
+        if let Some(map_file) = &self.map_file {
+            app.insert_resource(MapFile(map_file.clone()));
+        }
+        if let Some(save_map_file) = &self.save_map_file {
+            app.insert_resource(SaveMapFile(save_map_file.clone()))
+                .add_system(save_map);
+        }
+
+        app.init_resource::<MapSeed>()
+            .init_resource::<RoomTemplateRegistry>()
+            .init_resource::<MapConfig>()
+            .init_resource::<FloorNumber>()
+            .add_event::<FloorCleared>()
+            .register_ldtk_entity::<PlayerStartBundle>("PlayerStart")
+            .add_system(setup.in_schedule(OnEnter(AppState::Playing)))
+            .add_system(end_run.in_schedule(OnExit(AppState::GameOver)))
+            .add_system(end_run.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(
+                end_run
+                    .run_if(on_event::<RestartRun>())
+                    .in_schedule(OnExit(AppState::Paused)),
+            )
+            .add_systems(
+                (check_exit, next_floor)
+                    .chain()
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_systems((place_player, spawn_wall_colliders).in_set(OnUpdate(AppState::Playing)));
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let ldtk_files = generate_ldtk_files();
+/// The map of the current floor.
+#[derive(Resource, Debug, Clone)]
+pub struct CurrentMap(pub Map);
 
-    for (ldtk_file, offset) in ldtk_files {
+/// Spawns the first floor of a run, unless a run is already going.
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    seed: Res<MapSeed>,
+    map_file: Option<Res<MapFile>>,
+    current_map: Option<Res<CurrentMap>>,
+    registry: Res<RoomTemplateRegistry>,
+    mut config: ResMut<MapConfig>,
+) {
+    if current_map.is_some() {
+        return;
+    }
+
+    let mut rng = floor_rng(seed.0, 0);
+    let loaded = map_file.and_then(|map_file| match load_map(&map_file.0) {
+        Ok(map) => Some(map),
+        Err(e) => {
+            error!("{}, generating a map instead", e);
+            None
+        }
+    });
+    let map = loaded.unwrap_or_else(|| {
+        info!("Generating map with seed {}", seed.0);
+        generate_map(&config, &registry, &mut rng)
+    });
+
+    spawn_floor(
+        &mut commands,
+        &asset_server,
+        &registry,
+        &mut config,
+        map,
+        0,
+        &mut rng,
+    );
+}
+
+/// Saves the map of the floor that was just spawned.
+fn save_map(map: Option<Res<CurrentMap>>, save_map_file: Res<SaveMapFile>) {
+    let Some(map) = map.filter(|map| map.is_changed()) else {
+        return;
+    };
+
+    let path = &save_map_file.0;
+    match map.0.save(path) {
+        Ok(()) => info!("Saved map to {}", path.display()),
+        Err(e) => error!("Failed to save map to {}: {}", path.display(), e),
+    }
+}
+
+/// Gets rid of the floor, so that the next run starts from scratch with a
+/// new seed.
+fn end_run(
+    mut commands: Commands,
+    floor_entities: Query<Entity, With<FloorEntity>>,
+    mut floor: ResMut<FloorNumber>,
+    mut config: ResMut<MapConfig>,
+    mut seed: ResMut<MapSeed>,
+) {
+    for entity in floor_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<CurrentMap>();
+
+    floor.0 = 0;
+    let initial = MapConfig::default();
+    config.width = initial.width;
+    config.height = initial.height;
+    *seed = MapSeed::default();
+}
+
+/// Random numbers for the given floor of the run started with `seed`.
+/// The first floor uses `seed` itself.
+pub(super) fn floor_rng(seed: u64, floor: u32) -> StdRng {
+    StdRng::seed_from_u64(seed ^ (floor as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+pub(super) fn generate_map<R: Rng + ?Sized>(
+    config: &MapConfig,
+    registry: &RoomTemplateRegistry,
+    rng: &mut R,
+) -> Map {
+    let mut map = Map::with_rng(config.width, config.height, rng);
+    map.connect_side_rooms(rng, |openings| registry.has(RoomKind::Side { openings }));
+    map
+}
+
+/// Spawns the rooms of `map` and its exit, all tagged with `FloorEntity`.
+/// Rooms are picked among the templates up to `difficulty`.
+pub(super) fn spawn_floor<R: Rng + ?Sized>(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    registry: &RoomTemplateRegistry,
+    config: &mut MapConfig,
+    map: Map,
+    difficulty: u32,
+    rng: &mut R,
+) {
+    // A loaded map may not have the configured size
+    config.width = map.width;
+    config.height = map.height;
+
+    let ldtk_files = generate_ldtk_files(&map, registry, difficulty, rng);
+
+    for (ldtk_file, pos) in ldtk_files {
         // Load the LDtk level
         let level_asset = asset_server.load(ldtk_file);
 
         // Load the level using the LDtk loader
+        let offset = config.grid_to_world(pos).extend(0.);
         let level_bundle = LdtkWorldBundle {
             ldtk_handle: level_asset,
-            transform: Transform::from_translation(offset.mul(0.7))
-                .with_scale(Vec3::new(0.7, 0.7, 1.)), /*.with_scale(Vec3::new(0.25, 0.25, 0.25))*/
+            transform: Transform::from_translation(offset).with_scale(Vec3::new(
+                config.scale,
+                config.scale,
+                1.,
+            )),
             ..Default::default()
         };
 
         // Spawn the level
-        commands.spawn(level_bundle);
+        let mut level = commands.spawn((level_bundle, FloorEntity));
+        if pos == map.start {
+            level.insert(EntranceRoom);
+        }
     }
+
+    commands.spawn(ExitSensorBundle::new(config, map.end));
+    commands.spawn(KillVolumeBundle::new(config));
+    commands.insert_resource(CurrentMap(map));
 }
 
-fn generate_ldtk_files() -> Vec<(String, Vec3)> {
-    let map = Map::new(3, 3);
+fn load_map(path: &Path) -> Result<Map, String> {
+    info!("Loading map from {}", path.display());
+
+    let map = Map::load(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+    map.validate()
+        .map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+    Ok(map)
+}
+
+fn generate_ldtk_files<R: Rng + ?Sized>(
+    map: &Map,
+    registry: &RoomTemplateRegistry,
+    difficulty: u32,
+    rng: &mut R,
+) -> Vec<(String, (u32, u32))> {
     let mut ldtk_files = Vec::new();
 
     let current_dir = current_dir().unwrap();
-    for (i, tile) in map.map_tiles.iter().enumerate() {
-        let rand = rand::random::<u32>() % 1;
-        let specific_file_path = match tile {
-            MapTile::Entrance { pos, to } => {
-                format!("map_assets/map/entrances/{}/{}.ldtk", to, rand)
-            }
-            MapTile::Exit { pos, from } => format!("map_assets/map/exits/{}/{}.ldtk", from, rand,),
-            MapTile::Path { pos, from, to } => {
-                format!("map_assets/map/{}-{}/{}.ldtk", from, to, rand)
-            }
-            MapTile::Empty { .. } => {
-                let from_dir = match rand::random::<u32>() % 3 {
-                    0 => "down",
-                    1 => "left",
-                    2 => "right",
-                    _ => "down",
-                };
-                let to_dir = match rand::random::<u32>() % 3 {
-                    0 => "up",
-                    1 => "left",
-                    2 => "right",
-                    _ => "up",
-                };
-                // format!("map_assets/map/{}-{}/{}.ldtk", from_dir, to_dir, rand)
-                format!("map_assets/map/entrances/0.ldtk")
-            }
+    for tile in map.map_tiles.iter() {
+        let Some(template) = registry.choose(tile, difficulty, rng) else {
+            error!("No room template for {:?}", tile);
+            continue;
         };
-        // let specific_file_path = "map_assets/map/entrances/0.ldtk";
-
-        let ldtk_file = format!("{}/{}", current_dir.display(), specific_file_path);
-        // make the level offset in a grid pattern
-        ldtk_files.push((
-            ldtk_file,
-            Vec3::new(
-                (i as u32 % map.width) as f32 * 1920.,
-                (i as u32 / map.width) as f32 * 1920.,
-                0.,
-            ),
-        ));
+
+        let ldtk_file = format!("{}/{}", current_dir.display(), template.path.display());
+        ldtk_files.push((ldtk_file, tile.pos()));
     }
 
     ldtk_files
@@ -1,8 +1,17 @@
+pub mod ascii;
+pub mod config;
+pub mod coords;
+pub mod file;
+pub mod floor;
 pub mod generator;
 pub mod ldtk_setup;
+pub mod spawn;
+pub mod templates;
+pub mod validation;
+pub mod walls;
 
 use bevy::{
-    prelude::{Color, Commands, Transform, Vec2},
+    prelude::{Color, Commands, Res, Resource, Transform, Vec2},
     sprite::{Sprite, SpriteBundle},
 };
 
@@ -13,6 +22,15 @@ use crate::entities::{
     collision::CollisionBundle,
 };
 
+/// Sandbox mode spawns the player at the origin, next to some debug
+/// geometry, instead of at the entrance of the map.
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct Sandbox(pub bool);
+
+pub fn in_sandbox(sandbox: Res<Sandbox>) -> bool {
+    sandbox.0
+}
+
 pub fn spawn_map(mut commands: Commands) {
     let floor = WallBundle::new(BlockBundle::new(
         SpriteBundle {
@@ -0,0 +1,94 @@
+use bevy::prelude::{
+    warn, Assets, Bundle, Children, Commands, Component, Entity, EventReader, GlobalTransform,
+    Handle, Query, Res, Transform, Vec2, With,
+};
+use bevy_ecs_ldtk::{prelude::LdtkEntity, LdtkLevel, LevelEvent};
+
+use crate::entities::{character::CharacterVelocity, player::Player};
+
+use super::{config::MapConfig, generator::Map, ldtk_setup::CurrentMap};
+
+/// Where the player appears in an entrance room, placed as a "Player" or
+/// "PlayerStart" entity in the LDtk template. Without one the player starts
+/// in the middle of the room, which may be inside a wall.
+#[derive(Component, Default, Debug, Clone)]
+pub struct PlayerStart;
+
+#[derive(Bundle, Default, LdtkEntity)]
+pub struct PlayerStartBundle {
+    player_start: PlayerStart,
+}
+
+/// The LDtk world of the entrance room of the current floor.
+#[derive(Component, Default, Debug, Clone)]
+pub struct EntranceRoom;
+
+/// Keeps the player still at the entrance until its room has loaded.
+#[derive(Component, Default, Debug, Clone)]
+pub struct AwaitingLevel;
+
+pub fn place_player(
+    mut commands: Commands,
+    mut level_events: EventReader<LevelEvent>,
+    mut player: Query<
+        (Entity, &mut Transform, &mut CharacterVelocity),
+        (With<Player>, With<AwaitingLevel>),
+    >,
+    entrance: Query<&Children, With<EntranceRoom>>,
+    levels: Query<&Handle<LdtkLevel>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+    starts: Query<&GlobalTransform, With<PlayerStart>>,
+    map: Option<Res<CurrentMap>>,
+    config: Res<MapConfig>,
+) {
+    // The world's level is spawned as its child
+    let entrance_levels: Vec<&str> = entrance
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter_map(|child| levels.get(*child).ok())
+        .filter_map(|level| level_assets.get(level))
+        .map(|level| level.level.iid.as_str())
+        .collect();
+    let entrance_loaded = level_events.iter().any(|event| match event {
+        LevelEvent::Transformed(iid) => entrance_levels.contains(&iid.as_str()),
+        _ => false,
+    });
+
+    let Ok((entity, mut transform, mut velocity)) = player.get_single_mut() else {
+        return;
+    };
+    *velocity = CharacterVelocity::default();
+    let Some(map) = map else {
+        return;
+    };
+
+    let position = if entrance_loaded {
+        commands.entity(entity).remove::<AwaitingLevel>();
+        entrance_position(&starts, &map.0, &config)
+    } else {
+        config.room_center(map.0.start)
+    };
+    set_position(&mut transform, position);
+}
+
+/// The `PlayerStart` in the entrance room of `map`, or the middle of that
+/// room. The room must have loaded.
+pub fn entrance_position(
+    starts: &Query<&GlobalTransform, With<PlayerStart>>,
+    map: &Map,
+    config: &MapConfig,
+) -> Vec2 {
+    starts
+        .iter()
+        .map(|start| start.translation().truncate())
+        .find(|start| config.world_to_grid(*start) == Some(map.start))
+        .unwrap_or_else(|| {
+            warn!("No PlayerStart in the entrance room, starting in its middle");
+            config.room_center(map.start)
+        })
+}
+
+fn set_position(transform: &mut Transform, position: Vec2) {
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
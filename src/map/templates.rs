@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::{error, warn, FromWorld, Resource, World};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use super::generator::{Direction, MapTile, Openings};
+
+pub const ROOM_TEMPLATE_DIR: &str = "map_assets/map";
+
+/// Optional file in `ROOM_TEMPLATE_DIR` overriding the defaults of single
+/// templates, keyed by their path relative to `ROOM_TEMPLATE_DIR`:
+///
+/// ```json
+/// { "up-down/1.ldtk": { "weight": 0.5, "difficulty": 2, "tags": ["spikes"] } }
+/// ```
+pub const ROOM_TEMPLATE_MANIFEST: &str = "manifest.json";
+
+/// What a template can be used for, taken from the folder it lives in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoomKind {
+    /// `entrances/{to}/`
+    Entrance { to: Direction },
+    /// `exits/{from}/`
+    Exit { from: Direction },
+    /// `{from}-{to}/`
+    Path { from: Direction, to: Direction },
+    /// `side/{openings}/`, e.g. `side/closed/` or `side/right-left/`.
+    Side { openings: Openings },
+}
+
+impl RoomKind {
+    pub fn for_tile(tile: &MapTile) -> Self {
+        match *tile {
+            MapTile::Entrance { to, .. } => RoomKind::Entrance { to },
+            MapTile::Exit { from, .. } => RoomKind::Exit { from },
+            MapTile::Path { from, to, .. } => RoomKind::Path { from, to },
+            MapTile::Empty { openings, .. } => RoomKind::Side { openings },
+        }
+    }
+
+    /// Parses the folder of a template, relative to `ROOM_TEMPLATE_DIR`.
+    fn from_dir(dir: &Path) -> Option<Self> {
+        let parts: Vec<&str> = dir.iter().filter_map(|part| part.to_str()).collect();
+        match parts.as_slice() {
+            ["entrances", to] => Some(RoomKind::Entrance {
+                to: to.parse().ok()?,
+            }),
+            ["exits", from] => Some(RoomKind::Exit {
+                from: from.parse().ok()?,
+            }),
+            ["side", openings] => Some(RoomKind::Side {
+                openings: openings.parse().ok()?,
+            }),
+            [path] => {
+                let (from, to) = path.split_once('-')?;
+                Some(RoomKind::Path {
+                    from: from.parse().ok()?,
+                    to: to.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Sides of the room that are open.
+    pub fn openings(&self) -> Vec<Direction> {
+        match *self {
+            RoomKind::Entrance { to } => vec![to],
+            RoomKind::Exit { from } => vec![from],
+            RoomKind::Path { from, to } => vec![from, to],
+            RoomKind::Side { openings } => openings.iter().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TemplateMeta {
+    pub weight: f32,
+    pub difficulty: u32,
+    pub tags: Vec<String>,
+}
+
+impl Default for TemplateMeta {
+    fn default() -> Self {
+        Self {
+            weight: 1.0,
+            difficulty: 0,
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoomTemplate {
+    /// Path of the `.ldtk` file, relative to the project root.
+    pub path: PathBuf,
+    pub kind: RoomKind,
+    pub meta: TemplateMeta,
+    /// `pxWid` and `pxHei` of the template's level.
+    pub size: (u32, u32),
+}
+
+/// The parts of an LDtk project the registry cares about.
+#[derive(Deserialize)]
+struct LdtkProject {
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize)]
+struct LdtkLevel {
+    #[serde(rename = "pxWid")]
+    px_wid: u32,
+    #[serde(rename = "pxHei")]
+    px_hei: u32,
+}
+
+fn level_size(path: &Path) -> io::Result<(u32, u32)> {
+    let project: LdtkProject = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let level = project
+        .levels
+        .first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no levels"))?;
+    Ok((level.px_wid, level.px_hei))
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RoomTemplateRegistry {
+    templates: Vec<RoomTemplate>,
+}
+
+impl FromWorld for RoomTemplateRegistry {
+    fn from_world(_world: &mut World) -> Self {
+        RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap_or_else(|e| {
+            error!(
+                "Failed to scan room templates in {}: {}",
+                ROOM_TEMPLATE_DIR, e
+            );
+            RoomTemplateRegistry::default()
+        })
+    }
+}
+
+impl RoomTemplateRegistry {
+    /// Collects every `.ldtk` file below `root`, sorted by path so that
+    /// seeded choices stay reproducible.
+    pub fn scan(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref();
+
+        let manifest: HashMap<String, TemplateMeta> =
+            match fs::read_to_string(root.join(ROOM_TEMPLATE_MANIFEST)) {
+                Ok(manifest) => serde_json::from_str(&manifest)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+                Err(e) => return Err(e),
+            };
+
+        let mut files = Vec::new();
+        collect_ldtk_files(root, &mut files)?;
+        files.sort();
+
+        let mut templates = Vec::new();
+        for path in files {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let Some(kind) = relative.parent().and_then(RoomKind::from_dir) else {
+                warn!(
+                    "Skipping room template in unknown folder: {}",
+                    path.display()
+                );
+                continue;
+            };
+            let size = match level_size(&path) {
+                Ok(size) => size,
+                Err(e) => {
+                    warn!("Skipping room template {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let key = relative.to_string_lossy().replace('\\', "/");
+            let meta = manifest.get(&key).cloned().unwrap_or_default();
+
+            templates.push(RoomTemplate {
+                path,
+                kind,
+                meta,
+                size,
+            });
+        }
+
+        Ok(Self { templates })
+    }
+
+    pub fn templates(&self) -> &[RoomTemplate] {
+        &self.templates
+    }
+
+    /// Size shared by all rooms, or `None` if there are no templates. Rooms
+    /// are laid out on a grid, so templates of another size are reported.
+    pub fn room_size(&self) -> Option<(u32, u32)> {
+        let size = self.templates.first()?.size;
+        for template in self.templates.iter().filter(|t| t.size != size) {
+            warn!(
+                "Room template {} is {:?} pixels, expected {:?}",
+                template.path.display(),
+                template.size,
+                size
+            );
+        }
+        Some(size)
+    }
+
+    pub fn has(&self, kind: RoomKind) -> bool {
+        self.candidates(kind).next().is_some()
+    }
+
+    pub fn candidates(&self, kind: RoomKind) -> impl Iterator<Item = &RoomTemplate> {
+        self.templates.iter().filter(move |t| t.kind == kind)
+    }
+
+    /// Picks a template for `tile` no harder than `max_difficulty`, weighted
+    /// by `TemplateMeta::weight`.
+    pub fn choose<R: Rng + ?Sized>(
+        &self,
+        tile: &MapTile,
+        max_difficulty: u32,
+        rng: &mut R,
+    ) -> Option<&RoomTemplate> {
+        let candidates: Vec<&RoomTemplate> = self
+            .candidates(RoomKind::for_tile(tile))
+            .filter(|t| t.meta.difficulty <= max_difficulty)
+            .collect();
+        candidates
+            .choose_weighted(rng, |t| t.meta.weight)
+            .ok()
+            .copied()
+    }
+}
+
+fn collect_ldtk_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_ldtk_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "ldtk") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn every_tile_has_a_template() {
+    use super::generator::seeded_map;
+
+    let registry = RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap();
+    for seed in 0..500 {
+        let map = seeded_map(6, 6, seed, |openings| {
+            registry.has(RoomKind::Side { openings })
+        });
+        for tile in map.map_tiles.iter() {
+            assert!(
+                registry.has(RoomKind::for_tile(tile)),
+                "seed {}: no template for {:?}",
+                seed,
+                tile
+            );
+        }
+    }
+}
+
+#[test]
+fn side_rooms_connect_with_the_shipped_templates() {
+    use super::generator::seeded_map;
+
+    let registry = RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap();
+    let mut connected = 0;
+    for seed in 0..100 {
+        let map = seeded_map(6, 6, seed, |openings| {
+            registry.has(RoomKind::Side { openings })
+        });
+        connected += map
+            .map_tiles
+            .iter()
+            .filter(|tile| match tile {
+                MapTile::Empty { openings, .. } => !openings.is_empty(),
+                _ => false,
+            })
+            .count();
+    }
+    assert!(connected > 0, "no side room was ever connected");
+}
+
+#[test]
+fn templates_are_read_from_ldtk() {
+    let registry = RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap();
+    assert!(!registry.templates().is_empty());
+    assert_eq!(registry.room_size(), Some((1920, 1920)));
+}
@@ -0,0 +1,262 @@
+use std::fmt::{Display, Formatter};
+
+use super::generator::{Direction, Map, MapTile};
+
+/// Something wrong with a `Map`, as found by `Map::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapIssue {
+    /// `map_tiles` doesn't hold exactly `width * height` tiles.
+    WrongTileCount {
+        expected: usize,
+        found: usize,
+    },
+    OutOfBounds {
+        pos: (u32, u32),
+    },
+    /// The tile at `index` claims to be at `pos`.
+    Misplaced {
+        index: usize,
+        pos: (u32, u32),
+    },
+    MissingEntrance,
+    MissingExit,
+    DuplicateEntrance {
+        positions: Vec<(u32, u32)>,
+    },
+    DuplicateExit {
+        positions: Vec<(u32, u32)>,
+    },
+    /// The entrance or exit isn't at `Map::start`/`Map::end`.
+    EndpointMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    /// `pos` opens on `side`, but the neighbour there doesn't open back.
+    BrokenLink {
+        pos: (u32, u32),
+        side: Direction,
+    },
+    /// `pos` opens on `side` towards the edge of the map, or a path room
+    /// leaves through the side it came in from.
+    DeadEnd {
+        pos: (u32, u32),
+        side: Direction,
+    },
+    /// Following the path from the entrance never reaches the exit.
+    Unreachable,
+}
+
+impl Display for MapIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapIssue::WrongTileCount { expected, found } => {
+                write!(f, "expected {} tiles, found {}", expected, found)
+            }
+            MapIssue::OutOfBounds { pos } => write!(f, "tile at {:?} is out of bounds", pos),
+            MapIssue::Misplaced { index, pos } => {
+                write!(f, "tile {} claims position {:?}", index, pos)
+            }
+            MapIssue::MissingEntrance => write!(f, "no entrance"),
+            MapIssue::MissingExit => write!(f, "no exit"),
+            MapIssue::DuplicateEntrance { positions } => {
+                write!(f, "several entrances at {:?}", positions)
+            }
+            MapIssue::DuplicateExit { positions } => write!(f, "several exits at {:?}", positions),
+            MapIssue::EndpointMismatch { expected, found } => {
+                write!(f, "endpoint at {:?} instead of {:?}", found, expected)
+            }
+            MapIssue::BrokenLink { pos, side } => {
+                write!(f, "{:?} opens {} into a closed room", pos, side)
+            }
+            MapIssue::DeadEnd { pos, side } => write!(f, "{:?} opens {} into nothing", pos, side),
+            MapIssue::Unreachable => write!(f, "the exit can't be reached from the entrance"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub issues: Vec<MapIssue>,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid map:")?;
+        for issue in self.issues.iter() {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Whether `tile` is open on `side`.
+fn opens(tile: &MapTile, side: Direction) -> bool {
+    match *tile {
+        MapTile::Entrance { to, .. } => to == side,
+        MapTile::Exit { from, .. } => from == side,
+        MapTile::Path { from, to, .. } => from == side || to == side,
+        MapTile::Empty { openings, .. } => openings.contains(side),
+    }
+}
+
+fn is_side_room(tile: &MapTile) -> bool {
+    matches!(tile, MapTile::Empty { .. })
+}
+
+impl Map {
+    /// Checks that the map is a single path from `start` to `end`, with side
+    /// rooms only connected to each other. Every issue found is reported.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut issues = Vec::new();
+
+        let expected = (self.width * self.height) as usize;
+        if self.map_tiles.len() != expected {
+            issues.push(MapIssue::WrongTileCount {
+                expected,
+                found: self.map_tiles.len(),
+            });
+        }
+
+        let mut entrances = Vec::new();
+        let mut exits = Vec::new();
+        for (index, tile) in self.map_tiles.iter().enumerate() {
+            let pos = tile.pos();
+            if pos.0 >= self.width || pos.1 >= self.height {
+                issues.push(MapIssue::OutOfBounds { pos });
+                continue;
+            }
+            if (pos.1 * self.width + pos.0) as usize != index {
+                issues.push(MapIssue::Misplaced { index, pos });
+            }
+
+            match tile {
+                MapTile::Entrance { .. } => entrances.push(pos),
+                MapTile::Exit { .. } => exits.push(pos),
+                MapTile::Path { from, to, .. } if from == to => {
+                    issues.push(MapIssue::DeadEnd { pos, side: *to });
+                }
+                _ => {}
+            }
+
+            for side in [
+                Direction::Up,
+                Direction::Down,
+                Direction::Right,
+                Direction::Left,
+            ] {
+                if !opens(tile, side) {
+                    continue;
+                }
+                match side
+                    .step(pos, self.width, self.height)
+                    .and_then(|next| self.tile(next))
+                {
+                    None => issues.push(MapIssue::DeadEnd { pos, side }),
+                    Some(next) => {
+                        if !opens(next, side.opposite()) || is_side_room(tile) != is_side_room(next)
+                        {
+                            issues.push(MapIssue::BrokenLink { pos, side });
+                        }
+                    }
+                }
+            }
+        }
+
+        match entrances.as_slice() {
+            [] => issues.push(MapIssue::MissingEntrance),
+            [pos] if *pos != self.start => issues.push(MapIssue::EndpointMismatch {
+                expected: self.start,
+                found: *pos,
+            }),
+            [_] => {}
+            _ => issues.push(MapIssue::DuplicateEntrance {
+                positions: entrances,
+            }),
+        }
+        match exits.as_slice() {
+            [] => issues.push(MapIssue::MissingExit),
+            [pos] if *pos != self.end => issues.push(MapIssue::EndpointMismatch {
+                expected: self.end,
+                found: *pos,
+            }),
+            [_] => {}
+            _ => issues.push(MapIssue::DuplicateExit { positions: exits }),
+        }
+
+        if !self.exit_reachable() {
+            issues.push(MapIssue::Unreachable);
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { issues })
+        }
+    }
+
+    /// Follows `to` from `start` and reports whether it ends up at `end`.
+    fn exit_reachable(&self) -> bool {
+        let mut pos = self.start;
+        for _ in 0..self.map_tiles.len() {
+            let to = match self.tile(pos) {
+                Some(MapTile::Exit { .. }) => return pos == self.end,
+                Some(MapTile::Entrance { to, .. }) | Some(MapTile::Path { to, .. }) => *to,
+                _ => return false,
+            };
+            match to.step(pos, self.width, self.height) {
+                Some(next) => pos = next,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Panics with every issue found if the map is invalid, in debug builds only.
+    pub(super) fn debug_validate(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.validate() {
+                panic!("{}", e);
+            }
+        }
+    }
+}
+
+#[test]
+fn generated_maps_are_valid() {
+    use super::generator::seeded_map;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    for seed in 0..5000 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (width, height) = (rng.gen_range(1..12), rng.gen_range(2..12));
+        let map = seeded_map(width, height, seed, |_| true);
+
+        if let Err(e) = map.validate() {
+            panic!("seed {} ({}x{}): {}", seed, width, height, e);
+        }
+    }
+}
+
+#[test]
+fn validate_reports_broken_maps() {
+    let mut map = Map::with_seed(5, 5, 7);
+    let start = map.start;
+    let (x, y) = map.end;
+    map.map_tiles[(y * map.width + x) as usize] = MapTile::Entrance {
+        pos: (x, y),
+        to: Direction::Down,
+    };
+
+    let issues = map.validate().unwrap_err().issues;
+    assert!(issues.contains(&MapIssue::MissingExit));
+    assert!(issues.contains(&MapIssue::DuplicateEntrance {
+        positions: vec![start, (x, y)]
+    }));
+    assert!(issues.contains(&MapIssue::DeadEnd {
+        pos: (x, y),
+        side: Direction::Down
+    }));
+    assert!(issues.contains(&MapIssue::Unreachable));
+}
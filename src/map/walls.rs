@@ -0,0 +1,249 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use bevy::prelude::{
+    Added, BuildChildren, Commands, Entity, Parent, Query, Transform, TransformBundle, Vec2,
+};
+use bevy_ecs_ldtk::{prelude::LayerMetadata, GridCoords};
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+use serde::Deserialize;
+
+use crate::entities::blocks::Wall;
+
+/// Wall cells `left..=right` by `bottom..=top`, in LDtk grid coordinates
+/// (`y` grows upwards).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallRect {
+    pub left: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub top: i32,
+}
+
+impl WallRect {
+    /// Size in cells.
+    pub fn size(&self) -> (i32, i32) {
+        (self.right - self.left + 1, self.top - self.bottom + 1)
+    }
+
+    /// Center in cells, from the bottom-left corner of the grid.
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(
+            (self.left + self.right + 1) as f32 / 2.,
+            (self.bottom + self.top + 1) as f32 / 2.,
+        )
+    }
+}
+
+/// Covers `cells` with rectangles: every row is split into runs of
+/// consecutive cells, and runs spanning the same columns on consecutive
+/// rows are merged.
+pub fn merge_wall_cells(cells: impl IntoIterator<Item = (i32, i32)>) -> Vec<WallRect> {
+    let mut rows: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (x, y) in cells {
+        rows.entry(y).or_default().push(x);
+    }
+    let mut ys: Vec<i32> = rows.keys().copied().collect();
+    ys.sort_unstable();
+
+    let mut rects = Vec::new();
+    // Rectangles that may still grow, keyed by their columns
+    let mut open: HashMap<(i32, i32), WallRect> = HashMap::new();
+
+    for y in ys {
+        let xs = rows.get_mut(&y).unwrap();
+        xs.sort_unstable();
+        xs.dedup();
+
+        let mut runs = Vec::new();
+        for &x in xs.iter() {
+            match runs.last_mut() {
+                Some((_, right)) if *right + 1 == x => *right = x,
+                _ => runs.push((x, x)),
+            }
+        }
+
+        let mut still_open = HashMap::new();
+        for (left, right) in runs {
+            let rect = match open.remove(&(left, right)) {
+                Some(rect) if rect.top + 1 == y => WallRect { top: y, ..rect },
+                closed => {
+                    rects.extend(closed);
+                    WallRect {
+                        left,
+                        right,
+                        bottom: y,
+                        top: y,
+                    }
+                }
+            };
+            still_open.insert((left, right), rect);
+        }
+
+        rects.extend(open.into_values());
+        open = still_open;
+    }
+    rects.extend(open.into_values());
+
+    rects.sort_unstable_by_key(|rect| (rect.bottom, rect.left));
+    rects
+}
+
+/// Gives the wall cells of newly spawned IntGrid layers one fixed collider
+/// per `WallRect`, instead of one per cell.
+pub fn spawn_wall_colliders(
+    mut commands: Commands,
+    walls: Query<(&GridCoords, &Parent), Added<Wall>>,
+    layers: Query<&LayerMetadata>,
+) {
+    let mut cells: HashMap<Entity, Vec<(i32, i32)>> = HashMap::new();
+    for (coords, layer) in walls.iter() {
+        cells
+            .entry(layer.get())
+            .or_default()
+            .push((coords.x, coords.y));
+    }
+
+    for (layer, cells) in cells {
+        let Ok(metadata) = layers.get(layer) else {
+            continue;
+        };
+        let grid_size = metadata.grid_size as f32;
+
+        commands.entity(layer).with_children(|layer| {
+            for rect in merge_wall_cells(cells) {
+                let (width, height) = rect.size();
+                layer.spawn((
+                    Wall,
+                    RigidBody::Fixed,
+                    Collider::cuboid(
+                        width as f32 * grid_size / 2.,
+                        height as f32 * grid_size / 2.,
+                    ),
+                    TransformBundle::from_transform(Transform::from_translation(
+                        (rect.center() * grid_size).extend(0.),
+                    )),
+                ));
+            }
+        });
+    }
+}
+
+/// The parts of an LDtk project `wall_cells` reads.
+#[derive(Deserialize)]
+struct LdtkProject {
+    levels: Vec<LdtkLevel>,
+}
+
+#[derive(Deserialize)]
+struct LdtkLevel {
+    #[serde(rename = "layerInstances", default)]
+    layer_instances: Vec<LdtkLayer>,
+}
+
+#[derive(Deserialize)]
+struct LdtkLayer {
+    #[serde(rename = "__type")]
+    layer_type: String,
+    #[serde(rename = "__cWid")]
+    width: i32,
+    #[serde(rename = "__cHei")]
+    height: i32,
+    #[serde(rename = "intGridCsv", default)]
+    int_grid: Vec<i32>,
+}
+
+/// Wall cells of the IntGrid layers of an LDtk file, in the grid coordinates
+/// of `merge_wall_cells`.
+pub fn wall_cells(path: &Path) -> io::Result<Vec<(i32, i32)>> {
+    let project: LdtkProject = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut cells = Vec::new();
+    for layer in project
+        .levels
+        .iter()
+        .flat_map(|level| level.layer_instances.iter())
+        .filter(|layer| layer.layer_type == "IntGrid")
+    {
+        for (i, value) in layer.int_grid.iter().enumerate() {
+            if *value == 1 {
+                // LDtk rows go downwards
+                let i = i as i32;
+                cells.push((i % layer.width, layer.height - 1 - i / layer.width));
+            }
+        }
+    }
+    Ok(cells)
+}
+
+#[test]
+fn merged_rects_cover_the_cells() {
+    use std::collections::HashSet;
+
+    let cells: HashSet<(i32, i32)> = [
+        (0, 0),
+        (1, 0),
+        (2, 0),
+        (0, 1),
+        (1, 1),
+        (2, 1),
+        (0, 2),
+        (4, 2),
+        (5, 2),
+    ]
+    .into_iter()
+    .collect();
+
+    let rects = merge_wall_cells(cells.iter().copied());
+    assert_eq!(
+        rects,
+        [
+            WallRect {
+                left: 0,
+                right: 2,
+                bottom: 0,
+                top: 1
+            },
+            WallRect {
+                left: 0,
+                right: 0,
+                bottom: 2,
+                top: 2
+            },
+            WallRect {
+                left: 4,
+                right: 5,
+                bottom: 2,
+                top: 2
+            },
+        ]
+    );
+
+    let covered: HashSet<(i32, i32)> = rects
+        .iter()
+        .flat_map(|rect| {
+            (rect.left..=rect.right)
+                .flat_map(move |x| (rect.bottom..=rect.top).map(move |y| (x, y)))
+        })
+        .collect();
+    assert_eq!(covered, cells);
+}
+
+/// Merging has to pay off on the shipped templates. `benches/wall_colliders.rs`
+/// measures what it saves when spawning them.
+#[test]
+fn wall_collider_counts() {
+    use super::templates::{RoomTemplateRegistry, ROOM_TEMPLATE_DIR};
+
+    let registry = RoomTemplateRegistry::scan(ROOM_TEMPLATE_DIR).unwrap();
+    let (mut total_cells, mut total_rects) = (0, 0);
+    for template in registry.templates() {
+        let cells = wall_cells(&template.path).unwrap();
+        let rects = merge_wall_cells(cells.iter().copied());
+
+        assert!(rects.len() <= cells.len());
+        total_cells += cells.len();
+        total_rects += rects.len();
+    }
+    assert!(total_rects * 2 < total_cells);
+}
@@ -0,0 +1,176 @@
+use bevy::{
+    prelude::{
+        default, App, AssetServer, BuildChildren, Color, Commands, Component, DespawnRecursiveExt,
+        Entity, EventWriter, Input, IntoSystemAppConfig, IntoSystemAppConfigs, IntoSystemConfig,
+        KeyCode, NextState, NodeBundle, OnEnter, OnExit, OnUpdate, Plugin, Query, Res, ResMut,
+        TextBundle, With,
+    },
+    text::TextStyle,
+    ui::{AlignItems, FlexDirection, JustifyContent, Size, Style, Val},
+};
+use bevy_rapier2d::prelude::RapierConfiguration;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    entities::player::{Player, PlayerInput},
+    map::floor::FloorNumber,
+    state::{AppState, RestartRun},
+};
+
+pub const MENU_FONT: &str = "fonts/DejaVuSans-Bold.ttf";
+
+/// Root of the menu currently on screen.
+#[derive(Component, Default, Debug, Clone)]
+pub struct MenuUi;
+
+/// Spawns a full screen menu: `title` over a line per entry of `lines`.
+pub fn spawn_menu(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    title: &str,
+    lines: &[&str],
+) {
+    let font = asset_server.load(MENU_FONT);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                ..default()
+            },
+            MenuUi,
+        ))
+        .with_children(|menu| {
+            menu.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 64.,
+                    color: Color::WHITE,
+                },
+            ));
+            for line in lines {
+                menu.spawn(TextBundle::from_section(
+                    *line,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 28.,
+                        color: Color::GRAY,
+                    },
+                ));
+            }
+        });
+}
+
+pub fn despawn_menu(mut commands: Commands, menus: Query<Entity, With<MenuUi>>) {
+    for menu in menus.iter() {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_menu(&mut commands, &asset_server, "Roguelike", &["Enter - Play"]);
+}
+
+fn main_menu(input: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<AppState>>) {
+    if input.just_pressed(KeyCode::Return) {
+        next_state.set(AppState::Playing);
+    }
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    floor: Res<FloorNumber>,
+) {
+    let reached = format!("You reached floor {}", floor.0 + 1);
+    spawn_menu(
+        &mut commands,
+        &asset_server,
+        "Game over",
+        &[&reached, "Enter - Play again", "Escape - Main menu"],
+    );
+}
+
+fn game_over(input: Res<Input<KeyCode>>, mut next_state: ResMut<NextState<AppState>>) {
+    if input.just_pressed(KeyCode::Return) {
+        next_state.set(AppState::Playing);
+    } else if input.just_pressed(KeyCode::Escape) {
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+fn pause(
+    player: Query<&ActionState<PlayerInput>, With<Player>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if player
+        .iter()
+        .any(|actions| actions.just_pressed(PlayerInput::Pause))
+    {
+        next_state.set(AppState::Paused);
+    }
+}
+
+/// Stops the physics while paused or on the game over screen. Everything else
+/// that moves only runs while `Playing`.
+fn stop_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_menu(
+        &mut commands,
+        &asset_server,
+        "Paused",
+        &["Escape - Resume", "R - Restart", "Q - Quit to menu"],
+    );
+}
+
+fn pause_menu(
+    input: Res<Input<KeyCode>>,
+    player: Query<&ActionState<PlayerInput>, With<Player>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut restart: EventWriter<RestartRun>,
+) {
+    if player
+        .iter()
+        .any(|actions| actions.just_pressed(PlayerInput::Pause))
+    {
+        next_state.set(AppState::Playing);
+    } else if input.just_pressed(KeyCode::R) {
+        restart.send(RestartRun);
+        next_state.set(AppState::Playing);
+    } else if input.just_pressed(KeyCode::Q) {
+        next_state.set(AppState::MainMenu);
+    }
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_main_menu.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_system(main_menu.in_set(OnUpdate(AppState::MainMenu)))
+            .add_system(despawn_menu.in_schedule(OnExit(AppState::MainMenu)))
+            .add_systems((stop_physics, spawn_game_over).in_schedule(OnEnter(AppState::GameOver)))
+            .add_system(game_over.in_set(OnUpdate(AppState::GameOver)))
+            .add_systems((resume_physics, despawn_menu).in_schedule(OnExit(AppState::GameOver)))
+            .add_event::<RestartRun>()
+            .add_system(pause.in_set(OnUpdate(AppState::Playing)))
+            .add_systems((stop_physics, spawn_pause_menu).in_schedule(OnEnter(AppState::Paused)))
+            .add_system(pause_menu.in_set(OnUpdate(AppState::Paused)))
+            .add_systems((resume_physics, despawn_menu).in_schedule(OnExit(AppState::Paused)));
+    }
+}
@@ -0,0 +1,16 @@
+use bevy::prelude::States;
+
+/// Where the game is at. A run starts when entering `Playing` from
+/// `MainMenu` or `GameOver`, and is cleaned up when leaving them.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Ends the paused run, so that a new one starts when going back to
+/// `Playing`.
+pub struct RestartRun;